@@ -0,0 +1,93 @@
+//! Compares the verification with the key parsed once against parsing it on every webhook.
+
+use bpay::c2b::webhook::verification::{Verifier, Verify};
+use criterion::{criterion_group, criterion_main, Criterion};
+use reqwest::header::{HeaderMap, HeaderValue};
+use ring::{rand, signature};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct TestData {
+    private_key: String,
+    cert_public: String,
+    cert_public_md5_hash: String,
+}
+
+/// Verifier parsing the certificate on every call, as done before the key was cached.
+struct Uncached {
+    cert_public: String,
+    cert_serial: String,
+}
+
+impl Verify<'_> for Uncached {
+    fn get_certificate(&self) -> &str {
+        &self.cert_public
+    }
+
+    fn get_certificate_serial(&self) -> &str {
+        &self.cert_serial
+    }
+}
+
+fn signed_headers(data: &TestData, body: &str) -> HeaderMap {
+    let (timestamp, nonce) = ("1646584911979", "5K8264ILTKCH16CQ2502SI8ZNMTM67VS");
+    let der = data
+        .private_key
+        .split('\n')
+        .filter(|line| !line.starts_with("-----"))
+        .collect::<String>();
+    let key_pair = signature::RsaKeyPair::from_der(&base64::decode(der).unwrap()).unwrap();
+    let mut signature = vec![0; key_pair.public_modulus_len()];
+    key_pair
+        .sign(
+            &signature::RSA_PKCS1_SHA256,
+            &rand::SystemRandom::new(),
+            format!("{timestamp}\n{nonce}\n{body}\n").as_bytes(),
+            &mut signature,
+        )
+        .unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert("BinancePay-Timestamp", HeaderValue::from_static(timestamp));
+    headers.insert("BinancePay-Nonce", HeaderValue::from_static(nonce));
+    headers.insert(
+        "BinancePay-Certificate-SN",
+        HeaderValue::from_str(&data.cert_public_md5_hash).unwrap(),
+    );
+    headers.insert(
+        "BinancePay-Signature",
+        HeaderValue::from_str(&base64::encode(signature)).unwrap(),
+    );
+    headers
+}
+
+fn verification(c: &mut Criterion) {
+    let data: TestData =
+        serde_json::from_str(&std::fs::read_to_string("data/key_pair.json").unwrap()).unwrap();
+    let body = r#"{"bizType":"PAY","data":"{\"merchantTradeNo\":\"9825382937292\"}","bizId":29383937493038367292,"bizStatus":"PAY_SUCCESS"}"#;
+    let headers = signed_headers(&data, body);
+
+    let cached =
+        Verifier::new(data.cert_public.clone(), data.cert_public_md5_hash.clone()).unwrap();
+    let uncached = Uncached {
+        cert_public: data.cert_public,
+        cert_serial: data.cert_public_md5_hash,
+    };
+
+    let mut group = c.benchmark_group("verify");
+    group.bench_function("cached key", |b| {
+        b.iter(|| cached.verify(&headers, body).unwrap())
+    });
+    group.bench_function("parsed key", |b| {
+        b.iter(|| uncached.verify(&headers, body).unwrap())
+    });
+    group.finish();
+
+    // The work saved on every webhook by the cached key.
+    c.bench_function("parse key", |b| {
+        b.iter(|| uncached.get_public_key().unwrap())
+    });
+}
+
+criterion_group!(benches, verification);
+criterion_main!(benches);
@@ -1,7 +1,8 @@
+use bpay::api::amount::Amount;
 use bpay::api::order::close::{Request as CloseOrderRequest, Response as CloseOrderResponse};
 use bpay::api::order::create::{
-    Currency, Env, Goods, GoodsCategory, GoodsType, Request as OrderRequest,
-    Response as OrderResponse, TerminalType,
+    Currency, Goods, GoodsCategory, GoodsType, Request as OrderRequest, Response as OrderResponse,
+    TerminalType,
 };
 use bpay::client::Client;
 use tokio;
@@ -10,21 +11,18 @@ async fn create_dummy_order(
     merchant_trade_no: &str,
     client: &Client,
 ) -> bpay::errors::Result<OrderResponse> {
-    let order = OrderRequest {
-        env: Env {
-            terminal_type: TerminalType::Web,
-        },
-        merchant_trade_no: merchant_trade_no.to_string(),
-        order_amount: 10.0,
-        currency: Currency::USDT,
-        goods: Goods {
-            goods_type: GoodsType::VirtualGoods,
-            goods_category: GoodsCategory::Electronics,
-            reference_goods_id: "sku1234".into(),
-            goods_name: "Laptop".into(),
-            goods_detail: None,
-        },
-    };
+    let order = OrderRequest::builder()
+        .terminal_type(TerminalType::Web)
+        .merchant_trade_no(merchant_trade_no)
+        .order_amount(Amount::from(10u32))
+        .currency(Currency::USDT)
+        .goods(Goods::new(
+            GoodsType::VirtualGoods,
+            GoodsCategory::Electronics,
+            "sku1234",
+            "Laptop",
+        ))
+        .build()?;
 
     Ok(order.create(client).await?)
 }
@@ -1,5 +1,6 @@
+use bpay::api::amount::Amount;
 use bpay::api::order::create::{
-    Currency, Env, Goods, GoodsCategory, GoodsType, Request as OrderRequest, TerminalType,
+    Currency, Goods, GoodsCategory, GoodsType, Request as OrderRequest, TerminalType,
 };
 use bpay::client::Client;
 use bpay::errors::Result;
@@ -8,21 +9,18 @@ use tokio;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let order = OrderRequest {
-        env: Env {
-            terminal_type: TerminalType::Web,
-        },
-        merchant_trade_no: create_nonce(10),
-        order_amount: 10.0,
-        currency: Currency::USDT,
-        goods: Goods {
-            goods_type: GoodsType::VirtualGoods,
-            goods_category: GoodsCategory::Electronics,
-            reference_goods_id: "sku1234".into(),
-            goods_name: "Laptop".into(),
-            goods_detail: None,
-        },
-    };
+    let order = OrderRequest::builder()
+        .terminal_type(TerminalType::Web)
+        .merchant_trade_no(create_nonce(10))
+        .order_amount(Amount::from(10u32))
+        .currency(Currency::USDT)
+        .goods(Goods::new(
+            GoodsType::VirtualGoods,
+            GoodsCategory::Electronics,
+            "sku1234",
+            "Laptop",
+        ))
+        .build()?;
 
     let client = Client::from_env();
     let create_order_result = order.create(&client).await?;
@@ -1,14 +1,12 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 
-use axum::http::HeaderMap;
-use axum::response::IntoResponse;
 use axum::routing::post;
 use axum::{Extension, Router};
+use bpay::c2b::webhook::handler::{NotificationAck, SharedVerifier, VerifiedNotification};
 use bpay::c2b::webhook::notification::Notification;
-use bpay::c2b::webhook::verification::{Verifier, Verify};
+use bpay::c2b::webhook::verification::Verifier;
 use bpay::client::Client;
-use reqwest::StatusCode;
-use tower::ServiceBuilder;
 
 #[tokio::main]
 async fn main() -> bpay::errors::Result<()> {
@@ -19,10 +17,9 @@ async fn main() -> bpay::errors::Result<()> {
 }
 
 async fn create_server(verifier: Verifier) {
-    let signature_verifier = std::sync::Arc::new(verifier);
     let app = Router::new()
         .route("/", post(notification_request_handler))
-        .layer(ServiceBuilder::new().layer(Extension(signature_verifier)));
+        .layer(Extension::<SharedVerifier>(Arc::new(verifier)));
 
     // Make sure you have added tls layer of security to your proxy server.
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
@@ -30,34 +27,21 @@ async fn create_server(verifier: Verifier) {
     server.serve(app.into_make_service()).await.unwrap();
 }
 
+/// Requests which aren't signed by Binance or can't be parsed never reach the handler.
 async fn notification_request_handler(
-    Extension(ref mut verifier): Extension<std::sync::Arc<Verifier>>,
-    headers: HeaderMap,
-    body: String,
-) -> impl IntoResponse {
-    if let Err(e) = verifier.verify(&headers, body.as_str()) {
-        println!("Encountered error while verifying the signature: {e}");
-        (StatusCode::BAD_REQUEST, "Undefined error".to_string())
-    } else {
-        if let Ok(notification) = Notification::try_from(body.as_str()) {
-            match notification {
-                Notification::Order {
-                    biz_id: _,
-                    biz_status: _,
-                    order_detail: _,
-                } => {
-                    // Do something with the order notification
-                }
-                _ => {
-                    // Do something with other variants
-                }
-            }
-            (StatusCode::OK, "OK".to_string())
-        } else {
-            (
-                StatusCode::BAD_REQUEST,
-                "Could not parse the body.".to_string(),
-            )
+    VerifiedNotification(notification): VerifiedNotification,
+) -> NotificationAck {
+    match notification {
+        Notification::Order {
+            biz_id: _,
+            biz_status: _,
+            order_detail: _,
+        } => {
+            // Do something with the order notification
         }
-    };
+        _ => {
+            // Do something with other variants
+        }
+    }
+    NotificationAck
 }
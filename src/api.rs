@@ -1,14 +1,16 @@
 //! Use Binance [`client::Client`] in conjunction with the Request Response structs.
 //! - [`order::create::Request`] and [`order::create::Response`]
 
+use self::validation::Validate;
 use self::webhook::certificate::{Certificate, CertificateResult};
 use crate::c2b::webhook::verification::Verifier;
 pub use crate::c2b::*;
 use crate::client;
 use crate::client::Client;
-use crate::errors::Result;
+use crate::errors::{BinanceContentError, Error, ErrorCode, Result};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum API {
     CreateOrder,
     QueryCertificate,
@@ -24,6 +26,22 @@ pub enum API {
     PayoutQuery,
 }
 
+impl API {
+    /// Whether sending the same request twice has no additional effect.
+    /// Requests to these endpoints are retried by default.
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            API::QueryCertificate
+                | API::QueryOrder
+                | API::BalanceQuery
+                | API::QueryTransfer
+                | API::QueryRefund
+                | API::PayoutQuery
+        )
+    }
+}
+
 impl From<API> for String {
     fn from(item: API) -> Self {
         String::from(match item {
@@ -43,14 +61,52 @@ impl From<API> for String {
     }
 }
 
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Status {
+    Success,
+    Fail,
+}
+
 /// Response format from the Binance Pay API.
-#[derive(Deserialize)]
-struct Response<T> {
-    data: T,
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Response<T> {
+    /// "SUCCESS" or "FAIL"
+    pub status: Status,
+
+    /// "000000" on success, the error code otherwise.
+    pub code: Option<ErrorCode>,
+
+    /// Only present when the status is "SUCCESS".
+    pub data: Option<T>,
+
+    /// Reason of the failure.
+    pub error_message: Option<String>,
+}
+
+impl<T> Response<T> {
+    /// Returns the data of a successful response,
+    /// a failed one is turned into [`Error::BinanceError`].
+    pub fn into_result(self) -> Result<T> {
+        match (self.status, self.data) {
+            (Status::Success, Some(data)) => Ok(data),
+            (Status::Success, None) => Err(Error::Msg(
+                "Successful response does not contain any data".into(),
+            )),
+            (Status::Fail, _) => Err(Error::BinanceError {
+                response: BinanceContentError {
+                    status: "FAIL".into(),
+                    code: self.code.unwrap_or(ErrorCode::Unknown(0)),
+                    error_message: self.error_message.unwrap_or_default(),
+                },
+            }),
+        }
+    }
 }
 
 #[async_trait::async_trait]
-pub trait Binance<D>: Serialize + Sized
+pub trait Binance<D>: Serialize + Validate + Sized
 where
     D: DeserializeOwned,
 {
@@ -59,10 +115,10 @@ where
         note = "Use respective action method instead, e.g. .create(), .query(), etc."
     )]
     async fn post(&self, client: &client::Client) -> Result<D> {
-        let response = client
-            .post_signed_s::<Response<D>, Self>(self.get_api().into(), Some(self))
-            .await?;
-        Ok(response.data)
+        self.validate()?;
+        client
+            .post_signed_s::<D, Self>(self.get_api(), Some(self))
+            .await
     }
 
     fn get_api(&self) -> API;
@@ -82,11 +138,12 @@ macro_rules! impl_binance {
             }
 
             impl $x::$y::Request {
+                /// Validates the request, see [`Validate`], then sends it.
                 pub async fn $y(&self, client: &Client) -> Result<$x::$y::Response>  {
-                    let response = client
-                        .post_signed_s::<Response<$x::$y::Response>, Self>(self.get_api().into(), Some(self))
-                        .await?;
-                    Ok(response.data)
+                    self.validate()?;
+                    client
+                        .post_signed_s::<$x::$y::Response, Self>(self.get_api(), Some(self))
+                        .await
                 }
             }
         )*
@@ -114,17 +171,19 @@ impl Binance<CertificateResult> for Certificate {
 }
 
 impl Certificate {
+    /// The last certificate of the response, see [`Certificate::fetch_all`] to get all of them.
     pub async fn fetch(&self, client: &Client) -> Result<CertificateResult> {
-        let mut response = client
-            .post_signed_s::<Response<Vec<CertificateResult>>, Certificate>(
-                API::QueryCertificate.into(),
-                Some(self),
-            )
-            .await?;
-        Ok(response
-            .data
+        self.fetch_all(client)
+            .await?
             .pop()
-            .expect("Couldn't find the certificate inside the response array"))
+            .ok_or_else(|| Error::Msg("No certificate found in the response".into()))
+    }
+
+    /// Every certificate currently used by Binance to sign the webhooks.
+    pub async fn fetch_all(&self, client: &Client) -> Result<Vec<CertificateResult>> {
+        client
+            .post_signed_s::<Vec<CertificateResult>, Certificate>(API::QueryCertificate, Some(self))
+            .await
     }
 }
 
@@ -152,6 +211,6 @@ pub async fn get_certificate(client: &Client) -> Result<CertificateResult> {
 impl Verifier {
     pub async fn from_api(client: &Client) -> Result<Self> {
         let certs = get_certificate(client).await?;
-        Ok(Verifier::from(certs))
+        Ok(Verifier::try_from(certs)?)
     }
 }
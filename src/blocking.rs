@@ -0,0 +1,174 @@
+//! Synchronous client for the code which doesn't run inside an async runtime.
+/*!
+Every request goes through the async [`client::Client`](crate::client::Client) driven by a
+runtime owned by the blocking client, hence signing, retries and error handling are shared.
+The blocking client must not be used from within an async context.
+
+```rust,no_run
+# use bpay::api::order::create::Request as OrderRequest;
+# use bpay::api::webhook::certificate::Certificate;
+# use bpay::blocking::Client;
+# fn main() -> bpay::errors::Result<()> {
+let client = Client::from_env();
+# let order = OrderRequest::builder().build()?;
+let order = client.create(&order)?;
+let certificate = client.fetch(&Certificate)?;
+# Ok(())
+# }
+```
+*/
+
+use crate::api::webhook::certificate::{Certificate, CertificateResult};
+use crate::api::{self, Binance};
+use crate::client;
+use crate::errors::Result;
+use serde::de::DeserializeOwned;
+use tokio::runtime::{Builder, Runtime};
+
+/// Blocking counterpart of [`client::Client`].
+pub struct Client {
+    inner: client::Client,
+    runtime: Runtime,
+}
+
+impl Client {
+    /// See [`client::Client::new`].
+    pub fn new(api_key: Option<String>, secret_key: Option<String>, host: String) -> Self {
+        Self::from_client(client::Client::new(api_key, secret_key, host)).unwrap()
+    }
+
+    /// See [`client::Client::from_env`].
+    pub fn from_env() -> Self {
+        Self::from_client(client::Client::from_env()).unwrap()
+    }
+
+    /// Wraps an async client, e.g. one configured through [`client::ClientBuilder`].
+    pub fn from_client(inner: client::Client) -> Result<Self> {
+        Ok(Self {
+            inner,
+            runtime: Builder::new_current_thread().enable_all().build()?,
+        })
+    }
+
+    /// Blocking version of [`Certificate::fetch`].
+    pub fn fetch(&self, certificate: &Certificate) -> Result<CertificateResult> {
+        self.runtime.block_on(certificate.fetch(&self.inner))
+    }
+
+    fn send<R, D>(&self, request: &R) -> Result<D>
+    where
+        R: Binance<D>,
+        D: DeserializeOwned,
+    {
+        request.validate()?;
+        self.runtime.block_on(
+            self.inner
+                .post_signed_s::<D, R>(request.get_api(), Some(request)),
+        )
+    }
+}
+
+/// Groups the requests by the action method [`impl_binance!`](crate::api) generates for them
+/// and adds the blocking version of each action to [`Client`].
+macro_rules! impl_blocking {
+    (
+        $(($method: ident, $action: ident, [$($x: ident::$y: ident),*])),*
+    ) => {
+        $(
+            #[doc = concat!("Requests which can be sent with [`Client::", stringify!($method), "`].")]
+            pub trait $action {
+                type Response: DeserializeOwned;
+            }
+
+            $(
+                impl $action for api::$x::$y::Request {
+                    type Response = api::$x::$y::Response;
+                }
+            )*
+        )*
+
+        impl Client {
+            $(
+                #[doc = concat!("Blocking version of the `.", stringify!($method), "()` request methods.")]
+                pub fn $method<R>(&self, request: &R) -> Result<R::Response>
+                where
+                    R: $action + Binance<R::Response>,
+                {
+                    self.send(request)
+                }
+            )*
+        }
+    };
+}
+
+impl_blocking!(
+    (create, Create, [order::create, sub_merchant::create]),
+    (
+        query,
+        Query,
+        [
+            order::query,
+            wallet_balance::query,
+            transfer::query,
+            refund::query,
+            payout::query
+        ]
+    ),
+    (close, Close, [order::close]),
+    (
+        initiate,
+        Initiate,
+        [refund::initiate, transfer::initiate, payout::initiate]
+    )
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::amount::Amount;
+    use crate::api::currency::Currency;
+    use crate::api::order::create::{
+        Goods, GoodsCategory, GoodsType, Request as OrderRequest, TerminalType,
+    };
+    use crate::api::order::query::{Request as QueryOrderRequest, Status};
+    use crate::errors::ErrorCode;
+    use crate::sandbox::Simulator;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_blocking_client() {
+        let simulator = Simulator::new("api_key", "api_secret")
+            .start()
+            .await
+            .unwrap();
+        let host = simulator.url();
+        tokio::task::spawn_blocking(move || {
+            let client = Client::new(Some("api_key".into()), Some("api_secret".into()), host);
+            let order = OrderRequest::builder()
+                .terminal_type(TerminalType::App)
+                .merchant_trade_no("9825382937292")
+                .order_amount(Amount::from(25u32))
+                .currency(Currency::BUSD)
+                .goods(Goods::new(
+                    GoodsType::TangibleGoods,
+                    GoodsCategory::FoodGroceryHealth,
+                    "7876763A3B",
+                    "Ice Cream",
+                ))
+                .build()
+                .unwrap();
+            let order = client.create(&order).unwrap();
+            let query = QueryOrderRequest::new(Some(order.prepay_id), None);
+            assert!(matches!(
+                client.query(&query).unwrap().status,
+                Status::Initial
+            ));
+            assert!(!client.fetch(&Certificate).unwrap().cert_serial.is_empty());
+
+            let query = QueryOrderRequest::new(Some("404".into()), None);
+            let error = client.query(&query).unwrap_err();
+            assert_eq!(error.code(), Some(ErrorCode::OrderNotFound));
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+//! Exact decimal amount shared by all the request, response and notification structs.
+/*!
+Binance Pay amounts carry up to 8 decimal places. [`Amount`] keeps them as an arbitrary precision
+decimal, so sums and comparisons are exact, e.g. the payout `totalAmount` against its details.
+
+Depending on the endpoint an amount is sent either as a JSON number, the default, or as a string,
+see [`string`]. Both forms are accepted when deserializing. Numbers are written and read with
+their exact decimal digits through a [`RawValue`], never through a float.
+
+Requests with more than [`PRECISION`] decimal places are rejected by their
+[`Validate`](crate::c2b::validation::Validate) impl, the amounts received from Binance are kept
+as sent.
+
+```
+# use bpay::c2b::amount::Amount;
+# use std::str::FromStr;
+let details = [Amount::from_str("110.3").unwrap(), Amount::from_str("90.1").unwrap()];
+let total: Amount = details.iter().cloned().sum();
+assert_eq!(total, Amount::from_str("200.4").unwrap());
+assert_eq!(Amount::from_str("0.000000001").unwrap().decimal_places(), 9);
+```
+*/
+
+use crate::errors::Error;
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use serde::de;
+use serde::ser;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::value::RawValue;
+use std::borrow::Cow;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+/// Number of decimal places supported by Binance Pay.
+pub const PRECISION: i64 = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Amount(BigDecimal);
+
+impl Amount {
+    pub fn new(value: BigDecimal) -> Self {
+        Self(value)
+    }
+
+    /// Rounds the value half to even to [`PRECISION`] decimal places.
+    pub fn rounded(value: BigDecimal) -> Self {
+        Self(value.with_scale_round(PRECISION, RoundingMode::HalfEven))
+    }
+
+    pub fn zero() -> Self {
+        Self(BigDecimal::zero())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn as_decimal(&self) -> &BigDecimal {
+        &self.0
+    }
+
+    /// Number of significant decimal places, trailing zeros excluded.
+    pub fn decimal_places(&self) -> i64 {
+        self.0.normalized().fractional_digit_count().max(0)
+    }
+
+    /// Formats with exactly [`PRECISION`] decimal places, e.g. `"0.01000000"`.
+    pub fn to_fixed(&self) -> String {
+        self.0
+            .with_scale_round(PRECISION, RoundingMode::HalfEven)
+            .to_plain_string()
+    }
+}
+
+impl FromStr for Amount {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BigDecimal::from_str(s.trim())
+            .map(Self)
+            .map_err(|e| Error::Msg(format!("{s} is not a valid amount: {e}")))
+    }
+}
+
+impl From<u64> for Amount {
+    fn from(value: u64) -> Self {
+        Self(BigDecimal::from(value))
+    }
+}
+
+impl From<u32> for Amount {
+    fn from(value: u32) -> Self {
+        Self(BigDecimal::from(value))
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.to_plain_string())
+    }
+}
+
+impl Add for Amount {
+    type Output = Amount;
+
+    fn add(self, rhs: Amount) -> Amount {
+        Amount(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Amount {
+    type Output = Amount;
+
+    fn sub(self, rhs: Amount) -> Amount {
+        Amount(self.0 - rhs.0)
+    }
+}
+
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Self {
+        Amount(iter.map(|amount| amount.0).sum())
+    }
+}
+
+impl<'a> Sum<&'a Amount> for Amount {
+    fn sum<I: Iterator<Item = &'a Amount>>(iter: I) -> Self {
+        Amount(iter.map(|amount| &amount.0).sum())
+    }
+}
+
+/// Serialized as a JSON number written with the plain decimal digits, e.g. `0.00000002`.
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RawValue::from_string(self.0.to_plain_string())
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+/// Accepts both JSON numbers and decimal strings, read from their text as Binance sent it.
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = Box::<RawValue>::deserialize(deserializer)?;
+        let text = match raw.get() {
+            text if text.starts_with('"') => {
+                Cow::Owned(serde_json::from_str::<String>(text).map_err(de::Error::custom)?)
+            }
+            text => Cow::Borrowed(text),
+        };
+        Amount::from_str(&text).map_err(de::Error::custom)
+    }
+}
+
+/// For the fields Binance sends as strings, use with `#[serde(with = "...")]`.
+pub mod string {
+    use super::Amount;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(amount: &Amount, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&amount.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Amount, D::Error> {
+        Amount::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_serialization() {
+        let amount = Amount::from_str("0.00000002").unwrap();
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "0.00000002");
+        assert_eq!(
+            serde_json::from_str::<Amount>("0.00000002").unwrap(),
+            amount
+        );
+        assert_eq!(
+            serde_json::from_str::<Amount>("\"0.00000002\"").unwrap(),
+            amount
+        );
+        assert_eq!(amount.to_string(), "0.00000002");
+        assert_eq!(Amount::from(2u32).to_fixed(), "2.00000000");
+    }
+
+    #[test]
+    fn test_amount_round_trip() {
+        for text in [
+            "0.00000001",
+            "0.1",
+            "12345678901234567890.12345678",
+            "99999999999999999999999",
+            "1.23456789",
+        ] {
+            let amount = serde_json::from_str::<Amount>(text).unwrap();
+            assert_eq!(amount.to_string(), text);
+            assert_eq!(serde_json::to_string(&amount).unwrap(), text);
+        }
+        for text in ["0.00000001", "0.1", "1.23456789", "25.00"] {
+            let amount = Amount::from_str(text).unwrap();
+            let value = serde_json::to_value(&amount).unwrap();
+            assert_eq!(serde_json::from_value::<Amount>(value).unwrap(), amount);
+        }
+
+        // Binance may send more digits than a request accepts, they are kept.
+        for text in ["0.123456789", "\"0.123456789\""] {
+            let amount = serde_json::from_str::<Amount>(text).unwrap();
+            assert_eq!(amount.to_string(), "0.123456789");
+        }
+        assert!(serde_json::from_str::<Amount>("true").is_err());
+        assert!(serde_json::from_str::<Amount>("\"abc\"").is_err());
+    }
+
+    #[test]
+    fn test_amount_precision() {
+        assert_eq!(Amount::from_str("1.123456789").unwrap().decimal_places(), 9);
+        assert_eq!(
+            Amount::from_str("1.12345678000").unwrap().decimal_places(),
+            8
+        );
+        assert_eq!(Amount::from(100u32).decimal_places(), 0);
+        assert_eq!(
+            Amount::rounded(BigDecimal::from_str("1.123456785").unwrap()),
+            Amount::from_str("1.12345678").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_exact_arithmetic() {
+        let total: Amount = ["0.1", "0.2"]
+            .iter()
+            .map(|amount| Amount::from_str(amount).unwrap())
+            .sum();
+        assert_eq!(total, Amount::from_str("0.3").unwrap());
+        assert_eq!(total - Amount::from_str("0.3").unwrap(), Amount::zero());
+    }
+}
@@ -0,0 +1,131 @@
+//! Currency shared by all the request, response and notification structs.
+/*!
+The assets known at the time of writing have their own variant, any other code falls back to
+[`Currency::Other`]. Codes must be in uppercase, invalid ones are rejected when parsed and when
+serialized, i.e. before the request is sent.
+
+```
+# use bpay::c2b::currency::Currency;
+# use std::str::FromStr;
+assert_eq!(Currency::from_str("BUSD").unwrap(), Currency::BUSD);
+assert_eq!(Currency::from_str("DOGE").unwrap(), Currency::Other("DOGE".into()));
+assert!(Currency::from_str("busd").is_err());
+```
+*/
+
+use crate::errors::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+macro_rules! currencies {
+    ($($variant: ident),+) => {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum Currency {
+            $($variant,)+
+            /// Any other asset, the code must be in uppercase.
+            Other(String),
+        }
+
+        impl Currency {
+            pub fn code(&self) -> &str {
+                match self {
+                    $(Currency::$variant => stringify!($variant),)+
+                    Currency::Other(code) => code,
+                }
+            }
+
+            fn known(code: &str) -> Option<Self> {
+                match code {
+                    $(stringify!($variant) => Some(Currency::$variant),)+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+currencies!(BUSD, USDT, USDC, BNB, BTC, ETH, MBOX);
+
+impl Currency {
+    /// Whether the code is non empty and made of uppercase letters and digits only.
+    pub fn is_valid(&self) -> bool {
+        is_valid_code(self.code())
+    }
+}
+
+fn is_valid_code(code: &str) -> bool {
+    !code.is_empty()
+        && code
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+impl FromStr for Currency {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !is_valid_code(s) {
+            return Err(Error::Msg(format!(
+                "{s:?} is not a valid currency, codes must be in uppercase"
+            )));
+        }
+        Ok(Self::known(s).unwrap_or_else(|| Currency::Other(s.to_string())))
+    }
+}
+
+impl TryFrom<&str> for Currency {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Fails for an invalid [`Currency::Other`] code.
+impl Serialize for Currency {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !self.is_valid() {
+            return Err(serde::ser::Error::custom(format!(
+                "{:?} is not a valid currency, codes must be in uppercase",
+                self.code()
+            )));
+        }
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_currency_serialization() {
+        assert_eq!(serde_json::to_string(&Currency::USDT).unwrap(), "\"USDT\"");
+        assert_eq!(
+            serde_json::from_str::<Currency>("\"EUR\"").unwrap(),
+            Currency::Other("EUR".into())
+        );
+        assert_eq!(
+            serde_json::from_str::<Currency>("\"MBOX\"").unwrap(),
+            Currency::MBOX
+        );
+        assert!(serde_json::from_str::<Currency>("\"usdt\"").is_err());
+        assert!(serde_json::to_string(&Currency::Other("usdt".into())).is_err());
+        assert!(serde_json::to_string(&Currency::Other("".into())).is_err());
+    }
+}
@@ -1,10 +1,13 @@
 //! Contains all the possible Serializable and Deserializable
 //! request and response structs inside respective modules.
+pub mod amount;
+pub mod currency;
 pub mod order;
 pub mod payout;
 pub mod refund;
 pub mod sub_merchant;
 pub mod transfer;
+pub mod validation;
 pub mod wallet_balance;
 pub mod webhook;
 
@@ -3,6 +3,7 @@
 //! The successful close result will be notified asynchronously
 //! through Order Notification Webhook with bizStatus = "PAY_CLOSED"
 
+use crate::c2b::validation::{Validate, Violations};
 use std::fmt;
 
 use serde::{
@@ -59,7 +60,6 @@ impl<'de> Visitor<'de> for ClosedStatusVisitor {
 
 impl Request {
     pub fn new(prepay_id: Option<String>, merchant_trade_no: Option<String>) -> Self {
-        assert!(prepay_id.is_some() || merchant_trade_no.is_some());
         Self {
             prepay_id,
             merchant_trade_no,
@@ -67,6 +67,12 @@ impl Request {
     }
 }
 
+impl Validate for Request {
+    fn check(&self, violations: &mut Violations) {
+        violations.order_id(self.prepay_id.as_deref(), self.merchant_trade_no.as_deref());
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 #[cfg(test)]
@@ -81,6 +87,16 @@ mod tests {
         }
     ));
 
+    #[test]
+    fn test_validate_real_length_prepay_id() {
+        assert!(Request::new(Some("29383937493038367292".into()), None)
+            .validate()
+            .is_ok());
+        assert!(Request::new(Some("2938-3937".into()), None)
+            .validate()
+            .is_err());
+    }
+
     #[test]
     fn test_deserialize_close_order_result() {
         let result_json_true = r#"true"#;
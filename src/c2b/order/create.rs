@@ -1,9 +1,15 @@
 //! Order request and response structure that's laid out in the API documentation.
 //! [Create Order V2 Documentation](https://developers.binance.com/docs/binance-pay/api-order-create-v2)
 
-use serde::{Deserialize, Serialize};
+use crate::c2b::amount::Amount;
+use crate::c2b::validation::{Validate, Violations};
+use crate::errors;
+use serde::{Deserialize, Serialize, Serializer};
+use std::str::FromStr;
 
-#[derive(Serialize, Deserialize, Debug)]
+pub use crate::c2b::currency::Currency;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TerminalType {
     /// The client-side terminal type is a mobile application.
@@ -114,13 +120,123 @@ pub struct Goods {
     pub goods_detail: Option<String>,
 }
 
-/// Order currency in upper case. only "BUSD","USDT","MBOX" can be accepted,
-/// fiat NOT supported.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-pub enum Currency {
-    BUSD,
-    USDT,
-    MBOX,
+/// Price of a single unit of the goods.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GoodsUnitAmount {
+    pub currency: Currency,
+    pub amount: Amount,
+}
+
+/// Entry of the `goodsDetails` list.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GoodsDetail {
+    pub goods_type: GoodsType,
+
+    /// Goods category id.
+    pub goods_category: GoodsCategory,
+
+    /// The unique ID to identify the goods.
+    pub reference_goods_id: String,
+
+    /// Goods name limited to 256 characters.
+    pub goods_name: String,
+
+    /// Goods detail limited to 256 characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_detail: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_unit_amount: Option<GoodsUnitAmount>,
+
+    /// Quantity of the goods, e.g. "2".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_quantity: Option<String>,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Name {
+    pub first_name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub middle_name: Option<String>,
+
+    pub last_name: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Buyer {
+    /// The unique ID of the buyer on the merchant side.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_buyer_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buyer_name: Option<Name>,
+
+    /// e.g. "86"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buyer_phone_country_code: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buyer_phone_no: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buyer_email: Option<String>,
+
+    /// Registration time of the buyer on the merchant side, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buyer_registration_time: Option<u64>,
+
+    /// e.g. "en-US"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buyer_browser_language: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub enum ShippingAddressType {
+    #[serde(rename = "01")]
+    Home,
+
+    #[serde(rename = "02")]
+    Business,
+
+    #[serde(rename = "03")]
+    Others,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ShippingAddress {
+    /// ISO 3166 country code, e.g. "SG".
+    pub region: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+
+    pub city: String,
+
+    pub address: String,
+
+    pub zip_code: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shipping_address_type: Option<ShippingAddressType>,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Shipping {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shipping_name: Option<Name>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shipping_address: Option<ShippingAddress>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shipping_phone_no: Option<String>,
 }
 
 #[derive(Serialize, Debug)]
@@ -134,30 +250,356 @@ pub struct Request {
     pub merchant_trade_no: String,
 
     /// Amount Range: `0.01` - `20000`
-    pub order_amount: f32,
+    pub order_amount: Amount,
 
     pub currency: Currency,
 
+    /// Order amount in fiat, Binance converts it into `currency`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_amount: Option<Amount>,
+
+    /// Fiat currency of `fiat_amount`, e.g. "EUR".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_currency: Option<Currency>,
+
     pub goods: Goods,
+
+    /// Goods of the order, replaces `goods` in v2.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goods_details: Option<Vec<GoodsDetail>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buyer: Option<Buyer>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shipping: Option<Shipping>,
+
+    /// Redirect URL of the hosted checkout after a successful payment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_url: Option<String>,
+
+    /// Redirect URL of the hosted checkout after the payment is canceled or expires.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancel_url: Option<String>,
+
+    /// Expiry of the order in milliseconds since the epoch, at most 1 hour from now.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_expire_time: Option<u64>,
+
+    /// Currencies the buyer is allowed to pay with, sent comma separated.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "comma_separated"
+    )]
+    pub support_pay_currency: Option<Vec<Currency>>,
+
+    /// Webhook URL of this order, overrides the one configured for the merchant.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+
+    /// Sent back as is in the notifications, maximum length 512.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pass_through_info: Option<String>,
+
+    /// The sub merchant the order is created for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub_merchant_id: Option<String>,
 }
 
-impl Default for Request {
-    fn default() -> Self {
+fn comma_separated<S: Serializer>(
+    currencies: &Option<Vec<Currency>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let currencies = currencies.as_deref().unwrap_or_default();
+    if let Some(currency) = currencies.iter().find(|currency| !currency.is_valid()) {
+        return Err(serde::ser::Error::custom(format!(
+            "{:?} is not a valid currency, codes must be in uppercase",
+            currency.code()
+        )));
+    }
+    let codes: Vec<&str> = currencies.iter().map(Currency::code).collect();
+    serializer.serialize_str(&codes.join(","))
+}
+
+impl Goods {
+    pub fn new(
+        goods_type: GoodsType,
+        goods_category: GoodsCategory,
+        reference_goods_id: impl Into<String>,
+        goods_name: impl Into<String>,
+    ) -> Self {
         Self {
-            env: Env {
-                terminal_type: TerminalType::Web,
-            },
-            merchant_trade_no: crate::utils::create_nonce(10),
-            order_amount: 10.0,
-            currency: Currency::USDT,
-            goods: Goods {
-                goods_type: GoodsType::VirtualGoods,
-                goods_category: GoodsCategory::Electronics,
-                reference_goods_id: "abcd".into(),
-                goods_name: "XYZ".into(),
-                goods_detail: None,
-            },
+            goods_type,
+            goods_category,
+            reference_goods_id: reference_goods_id.into(),
+            goods_name: goods_name.into(),
+            goods_detail: None,
+        }
+    }
+
+    /// Goods detail limited to 256 characters.
+    pub fn detail(mut self, goods_detail: impl Into<String>) -> Self {
+        self.goods_detail = Some(goods_detail.into());
+        self
+    }
+
+    fn check(&self, field: &str, violations: &mut Violations) {
+        violations.required(
+            &format!("{field}.referenceGoodsId"),
+            &self.reference_goods_id,
+        );
+        violations.max_len(
+            &format!("{field}.referenceGoodsId"),
+            &self.reference_goods_id,
+            64,
+        );
+        violations.required(&format!("{field}.goodsName"), &self.goods_name);
+        violations.max_len(&format!("{field}.goodsName"), &self.goods_name, 256);
+        if let Some(detail) = &self.goods_detail {
+            violations.max_len(&format!("{field}.goodsDetail"), detail, 256);
+        }
+    }
+}
+
+impl GoodsDetail {
+    fn check(&self, field: &str, violations: &mut Violations) {
+        violations.required(
+            &format!("{field}.referenceGoodsId"),
+            &self.reference_goods_id,
+        );
+        violations.max_len(
+            &format!("{field}.referenceGoodsId"),
+            &self.reference_goods_id,
+            64,
+        );
+        violations.required(&format!("{field}.goodsName"), &self.goods_name);
+        violations.max_len(&format!("{field}.goodsName"), &self.goods_name, 256);
+        if let Some(detail) = &self.goods_detail {
+            violations.max_len(&format!("{field}.goodsDetail"), detail, 256);
+        }
+        if let Some(unit_amount) = &self.goods_unit_amount {
+            violations.currency(
+                &format!("{field}.goodsUnitAmount.currency"),
+                &unit_amount.currency,
+            );
+            violations.precision(
+                &format!("{field}.goodsUnitAmount.amount"),
+                &unit_amount.amount,
+            );
+        }
+    }
+}
+
+impl Validate for Request {
+    fn check(&self, violations: &mut Violations) {
+        violations.alphanumeric("merchantTradeNo", &self.merchant_trade_no, 32);
+        violations.range(
+            "orderAmount",
+            &self.order_amount,
+            &Amount::from_str("0.01").unwrap(),
+            &Amount::from(20000u32),
+        );
+        violations.currency("currency", &self.currency);
+        if let Some(fiat_currency) = &self.fiat_currency {
+            violations.currency("fiatCurrency", fiat_currency);
+        }
+        if let Some(fiat_amount) = &self.fiat_amount {
+            violations.positive("fiatAmount", fiat_amount);
+        }
+        self.goods.check("goods", violations);
+        for (i, goods) in self.goods_details.iter().flatten().enumerate() {
+            goods.check(&format!("goodsDetails[{i}]"), violations);
+        }
+        for currency in self.support_pay_currency.iter().flatten() {
+            violations.currency("supportPayCurrency", currency);
         }
+        if let Some(info) = &self.pass_through_info {
+            violations.max_len("passThroughInfo", info, 512);
+        }
+    }
+}
+
+impl Request {
+    pub fn builder() -> RequestBuilder {
+        RequestBuilder::default()
+    }
+}
+
+/// Builds a validated [`Request`], the terminal type, merchant trade no,
+/// order amount, currency and goods are required.
+///
+/// ```
+/// # use bpay::api::order::create::{Currency, Goods, GoodsCategory, GoodsType, Request, TerminalType};
+/// # use bpay::c2b::amount::Amount;
+/// let order = Request::builder()
+///     .terminal_type(TerminalType::Web)
+///     .merchant_trade_no("9825382937292")
+///     .order_amount(Amount::from(10u32))
+///     .currency(Currency::USDT)
+///     .goods(Goods::new(
+///         GoodsType::VirtualGoods,
+///         GoodsCategory::Electronics,
+///         "sku1234",
+///         "Laptop",
+///     ))
+///     .return_url("https://example.com/paid")
+///     .build()
+///     .unwrap();
+/// assert!(Request::builder().build().is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct RequestBuilder {
+    terminal_type: Option<TerminalType>,
+    merchant_trade_no: Option<String>,
+    order_amount: Option<Amount>,
+    currency: Option<Currency>,
+    fiat: Option<(Amount, Currency)>,
+    goods: Option<Goods>,
+    goods_details: Vec<GoodsDetail>,
+    buyer: Option<Buyer>,
+    shipping: Option<Shipping>,
+    return_url: Option<String>,
+    cancel_url: Option<String>,
+    order_expire_time: Option<u64>,
+    support_pay_currency: Vec<Currency>,
+    webhook_url: Option<String>,
+    pass_through_info: Option<String>,
+    sub_merchant_id: Option<String>,
+}
+
+impl RequestBuilder {
+    pub fn terminal_type(mut self, terminal_type: TerminalType) -> Self {
+        self.terminal_type = Some(terminal_type);
+        self
+    }
+
+    pub fn merchant_trade_no(mut self, merchant_trade_no: impl Into<String>) -> Self {
+        self.merchant_trade_no = Some(merchant_trade_no.into());
+        self
+    }
+
+    pub fn order_amount(mut self, order_amount: Amount) -> Self {
+        self.order_amount = Some(order_amount);
+        self
+    }
+
+    pub fn currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    /// Amount of the order in fiat, e.g. 10 EUR.
+    pub fn fiat_amount(mut self, amount: Amount, currency: Currency) -> Self {
+        self.fiat = Some((amount, currency));
+        self
+    }
+
+    pub fn goods(mut self, goods: Goods) -> Self {
+        self.goods = Some(goods);
+        self
+    }
+
+    /// Adds an entry to the `goodsDetails` list.
+    pub fn goods_detail(mut self, goods: GoodsDetail) -> Self {
+        self.goods_details.push(goods);
+        self
+    }
+
+    pub fn buyer(mut self, buyer: Buyer) -> Self {
+        self.buyer = Some(buyer);
+        self
+    }
+
+    pub fn shipping(mut self, shipping: Shipping) -> Self {
+        self.shipping = Some(shipping);
+        self
+    }
+
+    pub fn return_url(mut self, url: impl Into<String>) -> Self {
+        self.return_url = Some(url.into());
+        self
+    }
+
+    pub fn cancel_url(mut self, url: impl Into<String>) -> Self {
+        self.cancel_url = Some(url.into());
+        self
+    }
+
+    /// Milliseconds since the epoch.
+    pub fn order_expire_time(mut self, order_expire_time: u64) -> Self {
+        self.order_expire_time = Some(order_expire_time);
+        self
+    }
+
+    /// Adds a currency the buyer is allowed to pay with.
+    pub fn support_pay_currency(mut self, currency: Currency) -> Self {
+        self.support_pay_currency.push(currency);
+        self
+    }
+
+    pub fn webhook_url(mut self, url: impl Into<String>) -> Self {
+        self.webhook_url = Some(url.into());
+        self
+    }
+
+    pub fn pass_through_info(mut self, info: impl Into<String>) -> Self {
+        self.pass_through_info = Some(info.into());
+        self
+    }
+
+    pub fn sub_merchant_id(mut self, sub_merchant_id: impl Into<String>) -> Self {
+        self.sub_merchant_id = Some(sub_merchant_id.into());
+        self
+    }
+
+    /// Fails with [`errors::Error::Validation`] if a required field is missing or the request is invalid.
+    pub fn build(self) -> errors::Result<Request> {
+        let mut violations = Violations::default();
+        let terminal_type = violations.present("env.terminalType", self.terminal_type);
+        let merchant_trade_no = violations.present("merchantTradeNo", self.merchant_trade_no);
+        let order_amount = violations.present("orderAmount", self.order_amount);
+        let currency = violations.present("currency", self.currency);
+        let goods = violations.present("goods", self.goods);
+        let (
+            Some(terminal_type),
+            Some(merchant_trade_no),
+            Some(order_amount),
+            Some(currency),
+            Some(goods),
+        ) = (
+            terminal_type,
+            merchant_trade_no,
+            order_amount,
+            currency,
+            goods,
+        )
+        else {
+            return Err(violations.into_error().into());
+        };
+
+        let (fiat_amount, fiat_currency) = self.fiat.unzip();
+        let request = Request {
+            env: Env { terminal_type },
+            merchant_trade_no,
+            order_amount,
+            currency,
+            fiat_amount,
+            fiat_currency,
+            goods,
+            goods_details: Some(self.goods_details).filter(|goods| !goods.is_empty()),
+            buyer: self.buyer,
+            shipping: self.shipping,
+            return_url: self.return_url,
+            cancel_url: self.cancel_url,
+            order_expire_time: self.order_expire_time,
+            support_pay_currency: Some(self.support_pay_currency)
+                .filter(|currencies| !currencies.is_empty()),
+            webhook_url: self.webhook_url,
+            pass_through_info: self.pass_through_info,
+            sub_merchant_id: self.sub_merchant_id,
+        };
+        request.validate()?;
+        Ok(request)
     }
 }
 
@@ -195,25 +637,89 @@ pub struct Response {
 mod tests {
 
     use crate::c2b::tests::test_request_serialize_deserialize;
+    use crate::errors::Error;
+    use std::str::FromStr;
 
     test_request_serialize_deserialize!(
         (
             test_serialize_create_order,
             r#"{"env":{"terminalType":"APP"},"merchantTradeNo":"9825382937292","orderAmount":25.00,"currency":"BUSD","goods":{"goodsType":"01","goodsCategory":"D000","referenceGoodsId":"7876763A3B","goodsName":"Ice Cream","goodsDetail":"Greentea ice cream cone"}}"#,
+            Request::builder()
+                .terminal_type(TerminalType::App)
+                .merchant_trade_no("9825382937292")
+                .order_amount(Amount::from_str("25.00").unwrap())
+                .currency(Currency::BUSD)
+                .goods(
+                    Goods::new(
+                        GoodsType::TangibleGoods,
+                        GoodsCategory::FoodGroceryHealth,
+                        "7876763A3B",
+                        "Ice Cream"
+                    )
+                    .detail("Greentea ice cream cone")
+                )
+                .build()
+                .unwrap()
+        ),
+        (
+            test_serialize_create_order_v2_fields,
+            r#"{"env":{"terminalType":"WEB"},"merchantTradeNo":"9825382937292","orderAmount":25.00,"currency":"USDT","fiatAmount":23.5,"fiatCurrency":"EUR","goods":{"goodsType":"02","goodsCategory":"6000","referenceGoodsId":"7876763A3B","goodsName":"Gems"},"goodsDetails":[{"goodsType":"02","goodsCategory":"6000","referenceGoodsId":"7876763A3B","goodsName":"Gems","goodsUnitAmount":{"currency":"USDT","amount":12.5},"goodsQuantity":"2"}],"buyer":{"referenceBuyerId":"buyer1","buyerName":{"firstName":"Jane","lastName":"Doe"},"buyerEmail":"jane@example.com"},"shipping":{"shippingAddress":{"region":"SG","city":"Singapore","address":"1 Raffles Place","zipCode":"048616","shippingAddressType":"02"}},"returnUrl":"https://example.com/paid","cancelUrl":"https://example.com/canceled","orderExpireTime":1646584911979,"supportPayCurrency":"USDT,BUSD","webhookUrl":"https://example.com/webhook","passThroughInfo":"{\"cart\":42}","subMerchantId":"10000001"}"#,
             Request {
                 env: Env {
-                    terminal_type: TerminalType::App,
+                    terminal_type: TerminalType::Web,
                 },
                 merchant_trade_no: "9825382937292".into(),
-                order_amount: 25.00,
-                currency: Currency::BUSD,
+                order_amount: Amount::from_str("25.00").unwrap(),
+                currency: Currency::USDT,
+                fiat_amount: Some(Amount::from_str("23.5").unwrap()),
+                fiat_currency: Some(Currency::Other("EUR".into())),
                 goods: Goods {
-                    goods_type: GoodsType::TangibleGoods,
-                    goods_category: GoodsCategory::FoodGroceryHealth,
+                    goods_type: GoodsType::VirtualGoods,
+                    goods_category: GoodsCategory::GameRecharge,
                     reference_goods_id: "7876763A3B".into(),
-                    goods_name: "Ice Cream".into(),
-                    goods_detail: Some("Greentea ice cream cone".into()),
+                    goods_name: "Gems".into(),
+                    goods_detail: None,
                 },
+                goods_details: Some(vec![GoodsDetail {
+                    goods_type: GoodsType::VirtualGoods,
+                    goods_category: GoodsCategory::GameRecharge,
+                    reference_goods_id: "7876763A3B".into(),
+                    goods_name: "Gems".into(),
+                    goods_detail: None,
+                    goods_unit_amount: Some(GoodsUnitAmount {
+                        currency: Currency::USDT,
+                        amount: Amount::from_str("12.5").unwrap(),
+                    }),
+                    goods_quantity: Some("2".into()),
+                }]),
+                buyer: Some(Buyer {
+                    reference_buyer_id: Some("buyer1".into()),
+                    buyer_name: Some(Name {
+                        first_name: "Jane".into(),
+                        middle_name: None,
+                        last_name: "Doe".into(),
+                    }),
+                    buyer_email: Some("jane@example.com".into()),
+                    ..Default::default()
+                }),
+                shipping: Some(Shipping {
+                    shipping_address: Some(ShippingAddress {
+                        region: "SG".into(),
+                        state: None,
+                        city: "Singapore".into(),
+                        address: "1 Raffles Place".into(),
+                        zip_code: "048616".into(),
+                        shipping_address_type: Some(ShippingAddressType::Business),
+                    }),
+                    ..Default::default()
+                }),
+                return_url: Some("https://example.com/paid".into()),
+                cancel_url: Some("https://example.com/canceled".into()),
+                order_expire_time: Some(1646584911979),
+                support_pay_currency: Some(vec![Currency::USDT, Currency::BUSD]),
+                webhook_url: Some("https://example.com/webhook".into()),
+                pass_through_info: Some("{\"cart\":42}".into()),
+                sub_merchant_id: Some("10000001".into()),
             }
         ),
         (
@@ -245,4 +751,81 @@ mod tests {
             }
         )
     );
+    fn builder() -> RequestBuilder {
+        Request::builder()
+            .terminal_type(TerminalType::Web)
+            .merchant_trade_no("9825382937292")
+            .order_amount(Amount::from(10u32))
+            .currency(Currency::USDT)
+            .goods(Goods::new(
+                GoodsType::VirtualGoods,
+                GoodsCategory::Others,
+                "sku",
+                "Gems",
+            ))
+    }
+
+    #[test]
+    fn test_builder_requires_fields() {
+        assert!(builder().build().is_ok());
+        let Err(Error::Validation(error)) = Request::builder()
+            .terminal_type(TerminalType::Web)
+            .currency(Currency::USDT)
+            .build()
+        else {
+            panic!("missing fields must be reported");
+        };
+        let fields: Vec<_> = error
+            .violations
+            .into_iter()
+            .map(|violation| violation.field)
+            .collect();
+        assert_eq!(fields, ["merchantTradeNo", "orderAmount", "goods"]);
+    }
+
+    #[test]
+    fn test_builder_validates_request() {
+        let Err(Error::Validation(error)) = builder()
+            .merchant_trade_no("order-1")
+            .order_amount(Amount::from(20001u32))
+            .currency(Currency::Other("busd".into()))
+            .goods(Goods::new(
+                GoodsType::VirtualGoods,
+                GoodsCategory::Others,
+                "sku",
+                "x".repeat(257),
+            ))
+            .build()
+        else {
+            panic!("invalid fields must be reported");
+        };
+        let fields: Vec<_> = error
+            .violations
+            .into_iter()
+            .map(|violation| violation.field)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "merchantTradeNo",
+                "orderAmount",
+                "currency",
+                "goods.goodsName"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_validates_precision() {
+        let Err(Error::Validation(error)) = builder()
+            .order_amount(Amount::from_str("1.000000001").unwrap())
+            .build()
+        else {
+            panic!("too many decimal places must be reported");
+        };
+        assert_eq!(
+            error.violations[0].to_string(),
+            "orderAmount: must have at most 8 decimal places"
+        );
+    }
 }
@@ -0,0 +1,310 @@
+//! Single view of an order's state, fed by both query results and webhook notifications.
+/*!
+Query results ([`query::Status`]) and order notifications ([`BizStatus`]) are mapped into one
+[`OrderState`]. Events can arrive late or twice, [`OrderLifecycle::apply`] ignores the ones which
+don't change the state, report a state the order already went past, or whose status is unknown,
+and rejects the impossible ones, e.g. a `PAY_CLOSED` notification for a paid order, instead of
+moving the order backwards.
+
+```
+# use bpay::api::order::lifecycle::{OrderEvent, OrderLifecycle, OrderState};
+# use bpay::api::order::query::Status;
+# use bpay::api::webhook::notification::order::BizStatus;
+let mut order = OrderLifecycle::new();
+order.apply(OrderEvent::Notified(BizStatus::PaySuccess)).unwrap();
+// The poll result was sent before the payment.
+assert!(order.apply(OrderEvent::Polled(Status::Pending)).is_err());
+assert!(order.apply(OrderEvent::Polled(Status::Paid)).unwrap().is_none());
+assert_eq!(order.state(), OrderState::Paid);
+
+// The notification was sent before the refund started.
+order.apply(OrderEvent::Polled(Status::Refunding)).unwrap();
+assert!(order.apply(OrderEvent::Notified(BizStatus::PaySuccess)).unwrap().is_none());
+assert_eq!(order.state(), OrderState::Refunding);
+```
+*/
+
+use super::query;
+use crate::c2b::webhook::notification::order::BizStatus;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    Initial,
+    Pending,
+    Paid,
+    /// Closed by a `PAY_CLOSED` notification, either canceled or expired.
+    Closed,
+    Canceled,
+    Expired,
+    Error,
+    Refunding,
+    Refunded,
+}
+
+impl OrderState {
+    /// Whether the order can move from `self` to `next`.
+    /// Staying in the same state is always allowed.
+    pub fn can_transition_to(&self, next: OrderState) -> bool {
+        use OrderState::*;
+        *self == next
+            || match self {
+                Initial => !matches!(next, Refunding | Refunded),
+                Pending => !matches!(next, Initial | Refunding | Refunded),
+                Paid => matches!(next, Refunding | Refunded),
+                Refunding => matches!(next, Paid | Refunded),
+                Refunded => matches!(next, Refunding),
+                Closed => matches!(next, Canceled | Expired),
+                Canceled | Expired | Error => false,
+            }
+    }
+
+    /// Whether no other state can follow.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            OrderState::Canceled | OrderState::Expired | OrderState::Error
+        )
+    }
+}
+
+impl From<query::Status> for OrderState {
+    fn from(status: query::Status) -> Self {
+        match status {
+            query::Status::Initial => OrderState::Initial,
+            query::Status::Pending => OrderState::Pending,
+            query::Status::Paid => OrderState::Paid,
+            query::Status::Canceled => OrderState::Canceled,
+            query::Status::Error => OrderState::Error,
+            query::Status::Refunding => OrderState::Refunding,
+            query::Status::Refunded => OrderState::Refunded,
+            query::Status::Expired => OrderState::Expired,
+        }
+    }
+}
+
+/// What made the order change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderEvent {
+    /// Status returned by the query order API.
+    Polled(query::Status),
+
+    /// `bizStatus` of an order notification.
+    Notified(BizStatus),
+}
+
+impl OrderEvent {
+    /// State the event moves the order to, `None` for a notification status this version
+    /// doesn't know.
+    pub fn state(&self) -> Option<OrderState> {
+        match *self {
+            OrderEvent::Polled(status) => Some(status.into()),
+            OrderEvent::Notified(BizStatus::PaySuccess) => Some(OrderState::Paid),
+            OrderEvent::Notified(BizStatus::PayClosed) => Some(OrderState::Closed),
+            OrderEvent::Notified(BizStatus::Unknown) => None,
+        }
+    }
+
+    /// Whether the event is a late report of a state the order already went past, e.g. a
+    /// `PAY_CLOSED` notification after a poll saw the order expired, or a `PAY_SUCCESS` one
+    /// after the refund started.
+    pub fn is_stale_for(&self, state: OrderState) -> bool {
+        use OrderState::*;
+        match (self, self.state()) {
+            (_, Some(Closed)) => matches!(state, Canceled | Expired),
+            (OrderEvent::Notified(_), Some(Paid)) => matches!(state, Refunding | Refunded),
+            // A poll seeing a refunding order paid again means the refund failed.
+            (OrderEvent::Polled(_), Some(Paid)) => state == Refunded,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub from: OrderState,
+    pub to: OrderState,
+    pub event: OrderEvent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("{event:?} can't move the order from {from:?} to {to:?}")]
+pub struct InvalidTransition {
+    pub from: OrderState,
+    pub to: OrderState,
+    pub event: OrderEvent,
+}
+
+/// Current state of an order and the transitions which led to it.
+#[derive(Debug, Clone)]
+pub struct OrderLifecycle {
+    state: OrderState,
+    history: Vec<Transition>,
+}
+
+impl Default for OrderLifecycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderLifecycle {
+    /// Lifecycle of a newly created order.
+    pub fn new() -> Self {
+        Self::from_state(OrderState::Initial)
+    }
+
+    /// Resumes the lifecycle of an order, e.g. with the state loaded from a database.
+    pub fn from_state(state: OrderState) -> Self {
+        Self {
+            state,
+            history: vec![],
+        }
+    }
+
+    pub fn state(&self) -> OrderState {
+        self.state
+    }
+
+    /// Transitions applied so far, oldest first.
+    pub fn history(&self) -> &[Transition] {
+        &self.history
+    }
+
+    /// Moves the order to the state of the event.
+    /// Returns `None` if the order already is in that state, the event is
+    /// [stale](OrderEvent::is_stale_for) or the status is unknown.
+    pub fn apply(&mut self, event: OrderEvent) -> Result<Option<Transition>, InvalidTransition> {
+        let Some(to) = event.state() else {
+            return Ok(None);
+        };
+        let from = self.state;
+        if from == to || event.is_stale_for(from) {
+            return Ok(None);
+        }
+        if !from.can_transition_to(to) {
+            return Err(InvalidTransition { from, to, event });
+        }
+        let transition = Transition { from, to, event };
+        self.state = to;
+        self.history.push(transition);
+        Ok(Some(transition))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_lifecycle() {
+        let mut order = OrderLifecycle::new();
+        let transition = order
+            .apply(OrderEvent::Polled(query::Status::Pending))
+            .unwrap()
+            .unwrap();
+        assert_eq!(transition.from, OrderState::Initial);
+        order
+            .apply(OrderEvent::Notified(BizStatus::PaySuccess))
+            .unwrap();
+        order
+            .apply(OrderEvent::Polled(query::Status::Refunding))
+            .unwrap();
+        order
+            .apply(OrderEvent::Polled(query::Status::Refunded))
+            .unwrap();
+        assert_eq!(order.state(), OrderState::Refunded);
+        assert_eq!(
+            order.history()[1].event,
+            OrderEvent::Notified(BizStatus::PaySuccess)
+        );
+        assert_eq!(order.history().len(), 4);
+    }
+
+    #[test]
+    fn test_rejects_impossible_transitions() {
+        let mut order = OrderLifecycle::from_state(OrderState::Paid);
+        let error = order
+            .apply(OrderEvent::Notified(BizStatus::PayClosed))
+            .unwrap_err();
+        assert_eq!(error.from, OrderState::Paid);
+        assert_eq!(error.to, OrderState::Closed);
+        assert!(order
+            .apply(OrderEvent::Polled(query::Status::Initial))
+            .is_err());
+        assert_eq!(order.state(), OrderState::Paid);
+        assert!(order.history().is_empty());
+
+        let mut order = OrderLifecycle::new();
+        order
+            .apply(OrderEvent::Notified(BizStatus::PayClosed))
+            .unwrap();
+        order
+            .apply(OrderEvent::Polled(query::Status::Expired))
+            .unwrap();
+        assert!(order
+            .apply(OrderEvent::Notified(BizStatus::PaySuccess))
+            .is_err());
+        assert!(order
+            .apply(OrderEvent::Notified(BizStatus::Unknown))
+            .unwrap()
+            .is_none());
+        assert!(order.state().is_final());
+    }
+
+    #[test]
+    fn test_late_closed_notifications_are_ignored() {
+        for status in [query::Status::Canceled, query::Status::Expired] {
+            // The poll saw the order closed before the notification arrived.
+            let mut order = OrderLifecycle::new();
+            order.apply(OrderEvent::Polled(status)).unwrap();
+            assert!(order
+                .apply(OrderEvent::Notified(BizStatus::PayClosed))
+                .unwrap()
+                .is_none());
+            assert_eq!(order.state(), status.into());
+            assert_eq!(order.history().len(), 1);
+
+            // The notification arrived first.
+            let mut order = OrderLifecycle::new();
+            order
+                .apply(OrderEvent::Notified(BizStatus::PayClosed))
+                .unwrap();
+            order.apply(OrderEvent::Polled(status)).unwrap().unwrap();
+            assert_eq!(order.state(), status.into());
+        }
+    }
+
+    #[test]
+    fn test_late_paid_notifications_are_ignored() {
+        for status in [query::Status::Refunding, query::Status::Refunded] {
+            // The polls saw the payment and the refund before the notification arrived.
+            let mut order = OrderLifecycle::from_state(OrderState::Pending);
+            order
+                .apply(OrderEvent::Polled(query::Status::Paid))
+                .unwrap();
+            order.apply(OrderEvent::Polled(status)).unwrap();
+            assert!(order
+                .apply(OrderEvent::Notified(BizStatus::PaySuccess))
+                .unwrap()
+                .is_none());
+            assert_eq!(order.state(), status.into());
+
+            // The notification arrived first.
+            let mut order = OrderLifecycle::from_state(OrderState::Pending);
+            order
+                .apply(OrderEvent::Notified(BizStatus::PaySuccess))
+                .unwrap();
+            order.apply(OrderEvent::Polled(status)).unwrap().unwrap();
+            assert_eq!(order.state(), status.into());
+        }
+
+        // Polled paid again, the refund failed.
+        let mut order = OrderLifecycle::from_state(OrderState::Refunding);
+        order
+            .apply(OrderEvent::Polled(query::Status::Paid))
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.state(), OrderState::Paid);
+    }
+}
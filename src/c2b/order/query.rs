@@ -1,5 +1,8 @@
 //! Query order API used for merchant/partner to query order status
 
+use crate::c2b::amount::Amount;
+use crate::c2b::currency::Currency;
+use crate::c2b::validation::{Validate, Violations};
 pub use crate::c2b::webhook::notification::order::TerminalType as TradeType;
 use serde::{Deserialize, Serialize};
 
@@ -16,7 +19,7 @@ pub struct Request {
     merchant_trade_no: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(test, derive(Serialize))]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Status {
@@ -30,6 +33,14 @@ pub enum Status {
     Expired,
 }
 
+impl Status {
+    /// Whether the order won't change anymore by itself, i.e. it isn't waiting for the payer
+    /// or for a refund to complete.
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, Status::Initial | Status::Pending | Status::Refunding)
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[cfg_attr(test, derive(Serialize))]
 #[serde(rename_all = "camelCase")]
@@ -53,10 +64,10 @@ pub struct Response {
     pub status: Status,
 
     /// order currency
-    pub currency: String,
+    pub currency: Currency,
 
     /// limitation refer to Create Order API order amount	order amount
-    pub total_fee: f64,
+    pub total_fee: Amount,
 
     /// product name
     pub product_name: String,
@@ -76,7 +87,6 @@ pub struct Response {
 
 impl Request {
     pub fn new(prepay_id: Option<String>, merchant_trade_no: Option<String>) -> Self {
-        assert!(prepay_id.is_some() || merchant_trade_no.is_some());
         Self {
             prepay_id,
             merchant_trade_no,
@@ -84,9 +94,16 @@ impl Request {
     }
 }
 
+impl Validate for Request {
+    fn check(&self, violations: &mut Violations) {
+        violations.order_id(self.prepay_id.as_deref(), self.merchant_trade_no.as_deref());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::c2b::tests::test_request_serialize_deserialize;
+    use std::str::FromStr;
     test_request_serialize_deserialize!(
         (
             test_query_order_serialization,
@@ -119,8 +136,8 @@ mod tests {
                 merchant_trade_no: "9825382937292".into(),
                 trade_type: TradeType::App,
                 status: Status::Paid,
-                currency: "EUR".into(),
-                total_fee: 10.88,
+                currency: Currency::Other("EUR".into()),
+                total_fee: Amount::from_str("10.88").unwrap(),
                 product_name: "Ice Cream".into(),
                 product_detail: "Greentea ice cream cone".into(),
                 open_user_id: "".into(),
@@ -129,4 +146,20 @@ mod tests {
             }
         )
     );
+
+    #[test]
+    fn test_validate_order_ids() {
+        assert!(Request::new(Some("29383937493038367292".into()), None)
+            .validate()
+            .is_ok());
+        let fields: Vec<_> = Request::new(Some("2938-3937".into()), Some("x".repeat(33)))
+            .validate()
+            .unwrap_err()
+            .violations
+            .into_iter()
+            .map(|violation| violation.field)
+            .collect();
+        assert_eq!(fields, ["prepayId", "merchantTradeNo"]);
+        assert!(Request::new(None, None).validate().is_err());
+    }
 }
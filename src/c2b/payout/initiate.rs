@@ -1,3 +1,7 @@
+use crate::c2b::amount::Amount;
+use crate::c2b::currency::Currency;
+use crate::c2b::validation::{Validate, Violations};
+use crate::errors;
 use serde::{Deserialize, Serialize};
 
 pub use crate::c2b::wallet_balance::query::WalletType as TransferMethod;
@@ -45,7 +49,7 @@ pub struct TransferDetailReq {
     pub receiver: String,
 
     /// The transfer value cannot be less than 2 USD Transfer amount.
-    pub transfer_amount: f64,
+    pub transfer_amount: Amount,
 
     pub transfer_method: TransferMethod,
 
@@ -68,10 +72,10 @@ pub struct Request {
     pub batch_name: String,
 
     /// Crypto token only, fiat NOT supported. All characters must be in uppercase	All the transfers under this batch must use the same currency.
-    pub currency: String,
+    pub currency: Currency,
 
     /// It must be equal to the sum of all the detail transfers.
-    pub total_amount: f64,
+    pub total_amount: Amount,
 
     /// The total number of transfers. It must be equal to the detail transfer count.
     pub total_number: u8,
@@ -95,9 +99,174 @@ pub struct Response {
     pub status: Status,
 }
 
+impl Validate for Request {
+    fn check(&self, violations: &mut Violations) {
+        violations.alphanumeric("requestId", &self.request_id, 32);
+        violations.required("batchName", &self.batch_name);
+        violations.max_len("batchName", &self.batch_name, 128);
+        violations.currency("currency", &self.currency);
+        if usize::from(self.total_number) != self.transfer_detail_list.len() {
+            violations.add(
+                "totalNumber",
+                "must be equal to the number of transfers in transferDetailList",
+            );
+        }
+        let total: Amount = self
+            .transfer_detail_list
+            .iter()
+            .map(|detail| &detail.transfer_amount)
+            .sum();
+        if total != self.total_amount {
+            violations.add(
+                "totalAmount",
+                "must be equal to the sum of the transfers in transferDetailList",
+            );
+        }
+        for (i, detail) in self.transfer_detail_list.iter().enumerate() {
+            let field = |name| format!("transferDetailList[{i}].{name}");
+            violations.alphanumeric(&field("merchantSendId"), &detail.merchant_send_id, 32);
+            violations.required(&field("receiver"), &detail.receiver);
+            violations.positive(&field("transferAmount"), &detail.transfer_amount);
+            if let Some(remark) = &detail.remark {
+                violations.max_len(&field("remark"), remark, 128);
+            }
+        }
+    }
+}
+
+impl TransferDetailReq {
+    pub fn new(
+        merchant_send_id: impl Into<String>,
+        receive_type: ReceiveType,
+        receiver: impl Into<String>,
+        transfer_amount: Amount,
+        transfer_method: TransferMethod,
+    ) -> Self {
+        Self {
+            merchant_send_id: merchant_send_id.into(),
+            receive_type,
+            receiver: receiver.into(),
+            transfer_amount,
+            transfer_method,
+            remark: None,
+        }
+    }
+
+    /// Maximum length 128.
+    pub fn remark(mut self, remark: impl Into<String>) -> Self {
+        self.remark = Some(remark.into());
+        self
+    }
+}
+
+impl Request {
+    pub fn builder() -> RequestBuilder {
+        RequestBuilder::default()
+    }
+}
+
+/// Builds a validated [`Request`], the total amount and number are computed from the transfers.
+///
+/// ```
+/// # use bpay::api::payout::initiate::{ReceiveType, Request, TransferDetailReq, TransferMethod};
+/// # use bpay::c2b::amount::Amount;
+/// # use bpay::c2b::currency::Currency;
+/// let payout = Request::builder()
+///     .request_id("payout1")
+///     .batch_name("rewards")
+///     .currency(Currency::USDT)
+///     .transfer(TransferDetailReq::new(
+///         "send1",
+///         ReceiveType::PayId,
+///         "354205155",
+///         Amount::from(10u32),
+///         TransferMethod::FundingWallet,
+///     ))
+///     .build()
+///     .unwrap();
+/// assert_eq!(payout.total_number, 1);
+/// ```
+#[derive(Default)]
+pub struct RequestBuilder {
+    request_id: Option<String>,
+    biz_scene: Option<BizScene>,
+    batch_name: Option<String>,
+    currency: Option<Currency>,
+    transfers: Vec<TransferDetailReq>,
+}
+
+impl RequestBuilder {
+    pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    pub fn biz_scene(mut self, biz_scene: BizScene) -> Self {
+        self.biz_scene = Some(biz_scene);
+        self
+    }
+
+    pub fn batch_name(mut self, batch_name: impl Into<String>) -> Self {
+        self.batch_name = Some(batch_name.into());
+        self
+    }
+
+    pub fn currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    /// Adds a transfer to the batch.
+    pub fn transfer(mut self, transfer: TransferDetailReq) -> Self {
+        self.transfers.push(transfer);
+        self
+    }
+
+    /// Fails with [`errors::Error::Validation`] if a required field is missing or the request is invalid.
+    pub fn build(self) -> errors::Result<Request> {
+        let mut violations = Violations::default();
+        let request_id = violations.present("requestId", self.request_id);
+        let batch_name = violations.present("batchName", self.batch_name);
+        let currency = violations.present("currency", self.currency);
+        if self.transfers.is_empty() {
+            violations.add("transferDetailList", "must contain at least one transfer");
+        }
+        let total_number = u8::try_from(self.transfers.len()).ok();
+        if total_number.is_none() {
+            violations.add("transferDetailList", "must contain at most 255 transfers");
+        }
+        let (Some(request_id), Some(batch_name), Some(currency), Some(total_number), false) = (
+            request_id,
+            batch_name,
+            currency,
+            total_number,
+            self.transfers.is_empty(),
+        ) else {
+            return Err(violations.into_error().into());
+        };
+
+        let request = Request {
+            request_id,
+            biz_scene: self.biz_scene,
+            batch_name,
+            currency,
+            total_amount: self
+                .transfers
+                .iter()
+                .map(|transfer| &transfer.transfer_amount)
+                .sum(),
+            total_number,
+            transfer_detail_list: self.transfers,
+        };
+        request.validate()?;
+        Ok(request)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::c2b::tests::test_request_serialize_deserialize;
+    use std::str::FromStr;
     test_request_serialize_deserialize!(
         (
             test_batch_payout_serialize,
@@ -132,14 +301,14 @@ mod tests {
             Request {
                 request_id: "samplerequest1234".to_string(),
                 batch_name: "sample batch".to_string(),
-                currency: "BUSD".to_string(),
-                total_amount: 200.4,
+                currency: Currency::BUSD,
+                total_amount: Amount::from_str("200.4").unwrap(),
                 total_number: 2,
                 biz_scene: Some(BizScene::Settlement),
                 transfer_detail_list: vec![
                     TransferDetailReq {
                         merchant_send_id: "22231313131".to_string(),
-                        transfer_amount: 110.3,
+                        transfer_amount: Amount::from_str("110.3").unwrap(),
                         receive_type: ReceiveType::PayId,
                         transfer_method: TransferMethod::SpotWallet,
                         receiver: "354205155".to_string(),
@@ -147,7 +316,7 @@ mod tests {
                     },
                     TransferDetailReq {
                         merchant_send_id: "21231313132".to_string(),
-                        transfer_amount: 90.1,
+                        transfer_amount: Amount::from_str("90.1").unwrap(),
                         receive_type: ReceiveType::PayId,
                         transfer_method: TransferMethod::SpotWallet,
                         receiver: "354205153".to_string(),
@@ -165,4 +334,67 @@ mod tests {
             }
         )
     );
+    #[test]
+    fn test_validate_payout() {
+        use crate::c2b::validation::Validate;
+
+        let detail = |amount: &str, remark: &str| TransferDetailReq {
+            merchant_send_id: "send1".into(),
+            receive_type: ReceiveType::PayId,
+            receiver: "354205155".into(),
+            transfer_amount: Amount::from_str(amount).unwrap(),
+            transfer_method: TransferMethod::FundingWallet,
+            remark: Some(remark.into()),
+        };
+        let mut request = Request {
+            request_id: "payout1".into(),
+            biz_scene: None,
+            batch_name: "batch".into(),
+            currency: Currency::USDT,
+            total_amount: Amount::from_str("0.3").unwrap(),
+            total_number: 2,
+            transfer_detail_list: vec![detail("0.1", "ok"), detail("0.2", "ok")],
+        };
+        assert!(request.validate().is_ok());
+
+        request.transfer_detail_list[1] = detail("0.3", &"x".repeat(129));
+        let fields: Vec<_> = request
+            .validate()
+            .unwrap_err()
+            .violations
+            .into_iter()
+            .map(|violation| violation.field)
+            .collect();
+        assert_eq!(fields, ["totalAmount", "transferDetailList[1].remark"]);
+    }
+
+    #[test]
+    fn test_payout_builder_computes_totals() {
+        let transfer = |id: &str, amount: &str| {
+            TransferDetailReq::new(
+                id,
+                ReceiveType::Email,
+                "someone@example.com",
+                Amount::from_str(amount).unwrap(),
+                TransferMethod::SpotWallet,
+            )
+        };
+        let request = Request::builder()
+            .request_id("payout1")
+            .batch_name("rewards")
+            .currency(Currency::USDT)
+            .transfer(transfer("send1", "0.1"))
+            .transfer(transfer("send2", "0.2").remark("bonus"))
+            .build()
+            .unwrap();
+        assert_eq!(request.total_amount, Amount::from_str("0.3").unwrap());
+        assert_eq!(request.total_number, 2);
+
+        assert!(Request::builder()
+            .request_id("payout1")
+            .batch_name("rewards")
+            .currency(Currency::USDT)
+            .build()
+            .is_err());
+    }
 }
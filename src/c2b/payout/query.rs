@@ -1,5 +1,8 @@
 //! Payout query API used for Merchant/Partner to query transfer status.
 
+use crate::c2b::amount::{self, Amount};
+use crate::c2b::currency::Currency;
+use crate::c2b::validation::{Validate, Violations};
 use serde::{Deserialize, Serialize};
 
 pub use crate::c2b::payout::initiate::ReceiveType;
@@ -26,8 +29,17 @@ pub struct Request {
     detail_status: Option<DetailStatus>,
 }
 
-#[derive(Deserialize, Debug, Eq, PartialEq)]
-#[cfg_attr(test, derive(Serialize))]
+impl Request {
+    pub fn new(request_id: String, detail_status: Option<DetailStatus>) -> Self {
+        Self {
+            request_id,
+            detail_status,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum BatchStatus {
     Accepted,
@@ -36,6 +48,24 @@ pub enum BatchStatus {
     PartSuccess,
     Failed,
     Canceled,
+
+    /// A status this version doesn't know.
+    #[serde(other)]
+    Unknown,
+}
+
+impl BatchStatus {
+    /// Unknown statuses aren't terminal, a new one may be an intermediate state, so polling
+    /// keeps waiting until its deadline.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            BatchStatus::Success
+                | BatchStatus::PartSuccess
+                | BatchStatus::Failed
+                | BatchStatus::Canceled
+        )
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -63,7 +93,8 @@ pub struct TransferDetailResult {
     pub payer_id: u64,
 
     ///	Amount transferred.
-    pub amount: String,
+    #[serde(with = "amount::string")]
+    pub amount: Amount,
 
     ///	Enum string
     pub receive_type: ReceiveType,
@@ -96,11 +127,12 @@ pub struct Response {
     pub merchant_id: u64,
 
     /// The request currency.
-    pub currency: String,
+    pub currency: Currency,
 
     /// Total transfer amount in this batch.
-    /// Ambiguous: sample result response has type string but type mentioned is decimal.
-    pub total_amount: f64,
+    /// Ambiguous: sample result response has type string but type mentioned is decimal,
+    /// both are accepted.
+    pub total_amount: Amount,
 
     /// Total number of transfers in the batch.
     pub total_number: u16,
@@ -109,9 +141,16 @@ pub struct Response {
     pub transfer_detail_list: Vec<TransferDetailResult>,
 }
 
+impl Validate for Request {
+    fn check(&self, violations: &mut Violations) {
+        violations.alphanumeric("requestId", &self.request_id, 32);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::c2b::tests::test_request_serialize_deserialize;
+    use std::str::FromStr;
 
     test_request_serialize_deserialize!(
         (
@@ -164,15 +203,15 @@ mod tests {
                 request_id: "payoutqueryrequest1232455".to_string(),
                 batch_status: BatchStatus::Accepted,
                 merchant_id: 354195960,
-                currency: "USDT".to_string(),
-                total_amount: 0.00000002,
+                currency: Currency::USDT,
+                total_amount: Amount::from_str("0.00000002").unwrap(),
                 total_number: 2,
                 transfer_detail_list: vec![
                     TransferDetailResult {
                         order_id: 118073402258677760,
                         merchant_send_id: "21231313131".to_string(),
                         payer_id: 354195960,
-                        amount: "0.00000001".to_string(),
+                        amount: Amount::from_str("0.00000001").unwrap(),
                         receive_type: ReceiveType::PayId,
                         receiver: "354205155".to_string(),
                         payee_id: 354205155,
@@ -184,7 +223,7 @@ mod tests {
                         order_id: 118073402258677760,
                         merchant_send_id: "21231313132".to_string(),
                         payer_id: 354195960,
-                        amount: "0.00000001".to_string(),
+                        amount: Amount::from_str("0.00000001").unwrap(),
                         receive_type: ReceiveType::PayId,
                         receiver: "354205155".to_string(),
                         payee_id: 354205155,
@@ -196,4 +235,13 @@ mod tests {
             }
         )
     );
+
+    #[test]
+    fn test_unknown_batch_status_is_not_terminal() {
+        let status: BatchStatus = serde_json::from_str(r#""QUEUED""#).unwrap();
+        assert_eq!(status, BatchStatus::Unknown);
+        assert!(!status.is_terminal());
+        assert!(!BatchStatus::Processing.is_terminal());
+        assert!(BatchStatus::PartSuccess.is_terminal());
+    }
 }
@@ -1,5 +1,8 @@
 //! Refund order API used for Merchant/Partner to refund for a successful payment.
 
+use crate::c2b::amount::{self, Amount};
+use crate::c2b::validation::{Validate, Violations};
+use crate::errors;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Debug)]
@@ -12,15 +15,15 @@ pub struct Request {
     pub prepay_id: String,
 
     /// You can perform multiple partial refunds, but their sum should not exceed the order amount.
-    pub refund_amount: f64,
+    pub refund_amount: Amount,
 
     /// Reason of the refund.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub refund_reason: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
-#[cfg_attr(test, derive(Serialize))]
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
 pub enum RefundDuplicateStatus {
     #[serde(rename = "Y")]
     Yes,
@@ -29,8 +32,8 @@ pub enum RefundDuplicateStatus {
     No,
 }
 
-#[derive(Deserialize, Debug)]
-#[cfg_attr(test, derive(Serialize))]
+#[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct Response {
     ///  The unique ID assigned by the merchant to identify a refund request.
@@ -40,13 +43,16 @@ pub struct Response {
     pub prepay_id: String,
 
     ///  The total amount of prepay order.
-    pub order_amount: String,
+    #[serde(with = "amount::string")]
+    pub order_amount: Amount,
 
     ///  The total refunded amount included this refund request.
-    pub refunded_amount: String,
+    #[serde(with = "amount::string")]
+    pub refunded_amount: Amount,
 
     ///  The refund amount of this refund request.
-    pub refund_amount: String,
+    #[serde(with = "amount::string")]
+    pub refund_amount: Amount,
 
     ///  The remaining attempts of this original order. If this value becomes 1, then your next refund request amount will be ignored. We will refund all the remaing amount of this original order.
     pub remaining_attempts: u8,
@@ -58,9 +64,81 @@ pub struct Response {
     pub duplicate_request: RefundDuplicateStatus,
 }
 
+impl Validate for Request {
+    fn check(&self, violations: &mut Violations) {
+        violations.required("refundRequestId", &self.refund_request_id);
+        violations.max_len("refundRequestId", &self.refund_request_id, 64);
+        violations.prepay_id(&self.prepay_id);
+        violations.positive("refundAmount", &self.refund_amount);
+        if let Some(reason) = &self.refund_reason {
+            violations.max_len("refundReason", reason, 256);
+        }
+    }
+}
+
+impl Request {
+    pub fn builder() -> RequestBuilder {
+        RequestBuilder::default()
+    }
+}
+
+/// Builds a validated [`Request`], only the refund reason is optional.
+#[derive(Debug, Default)]
+pub struct RequestBuilder {
+    refund_request_id: Option<String>,
+    prepay_id: Option<String>,
+    refund_amount: Option<Amount>,
+    refund_reason: Option<String>,
+}
+
+impl RequestBuilder {
+    pub fn refund_request_id(mut self, refund_request_id: impl Into<String>) -> Self {
+        self.refund_request_id = Some(refund_request_id.into());
+        self
+    }
+
+    pub fn prepay_id(mut self, prepay_id: impl Into<String>) -> Self {
+        self.prepay_id = Some(prepay_id.into());
+        self
+    }
+
+    pub fn refund_amount(mut self, refund_amount: Amount) -> Self {
+        self.refund_amount = Some(refund_amount);
+        self
+    }
+
+    pub fn refund_reason(mut self, refund_reason: impl Into<String>) -> Self {
+        self.refund_reason = Some(refund_reason.into());
+        self
+    }
+
+    /// Fails with [`errors::Error::Validation`] if a required field is missing or the request is invalid.
+    pub fn build(self) -> errors::Result<Request> {
+        let mut violations = Violations::default();
+        let refund_request_id = violations.present("refundRequestId", self.refund_request_id);
+        let prepay_id = violations.present("prepayId", self.prepay_id);
+        let refund_amount = violations.present("refundAmount", self.refund_amount);
+        let (Some(refund_request_id), Some(prepay_id), Some(refund_amount)) =
+            (refund_request_id, prepay_id, refund_amount)
+        else {
+            return Err(violations.into_error().into());
+        };
+
+        let request = Request {
+            refund_request_id,
+            prepay_id,
+            refund_amount,
+            refund_reason: self.refund_reason,
+        };
+        request.validate()?;
+        Ok(request)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::c2b::tests::test_request_serialize_deserialize;
+    use std::str::FromStr;
     test_request_serialize_deserialize!(
         (
             test_refund_order_serialize,
@@ -68,7 +146,7 @@ mod tests {
             Request {
                 refund_request_id: "68711039982968832".to_string(),
                 prepay_id: "383729303729303".to_string(),
-                refund_amount: 25.00,
+                refund_amount: Amount::from_str("25.00").unwrap(),
                 refund_reason: Some("".to_string()),
             }
         ),
@@ -78,13 +156,40 @@ mod tests {
             Response {
                 refund_request_id: "68711039982968832".to_string(),
                 prepay_id: "383729303729303".to_string(),
-                order_amount: "100.11".to_string(),
-                refunded_amount: "10.88".to_string(),
-                refund_amount: "5.00".to_string(),
+                order_amount: Amount::from_str("100.11").unwrap(),
+                refunded_amount: Amount::from_str("10.88").unwrap(),
+                refund_amount: Amount::from_str("5.00").unwrap(),
                 remaining_attempts: 8,
                 payer_open_id: "dde730c2e0ea1f1780cf26343b98fd3b".to_string(),
                 duplicate_request: RefundDuplicateStatus::No,
             }
         )
     );
+
+    #[test]
+    fn test_refund_builder() {
+        let request = Request::builder()
+            .refund_request_id("68711039982968832")
+            .prepay_id("383729303729303")
+            .refund_amount(Amount::from_str("25.00").unwrap())
+            .build()
+            .unwrap();
+        assert!(request.refund_reason.is_none());
+
+        let request = Request::builder()
+            .refund_request_id("68711039982968832")
+            .prepay_id("29383937493038367292")
+            .refund_amount(Amount::from_str("25.00").unwrap())
+            .build();
+        assert!(request.is_ok());
+
+        let error = Request::builder()
+            .prepay_id("383729303729303")
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid request: refundRequestId: is required, refundAmount: is required"
+        );
+    }
 }
@@ -1,5 +1,7 @@
 //! Refund order API used for Merchant/Partner to refund for a successful payment.
 
+use crate::c2b::amount::{self, Amount};
+use crate::c2b::validation::{Validate, Violations};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Debug)]
@@ -9,7 +11,7 @@ pub struct Request {
     pub refund_request_id: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(test, derive(Serialize))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RefundStatus {
@@ -18,6 +20,12 @@ pub enum RefundStatus {
     RefundPending,
 }
 
+impl RefundStatus {
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, RefundStatus::RefundPending)
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[cfg_attr(test, derive(Serialize))]
 #[serde(rename_all = "camelCase")]
@@ -29,13 +37,16 @@ pub struct Response {
     pub prepay_id: String,
 
     ///  The total amount of prepay order.
-    pub order_amount: String,
+    #[serde(with = "amount::string")]
+    pub order_amount: Amount,
 
     ///  The total refunded amount included this refund request.
-    pub refunded_amount: String,
+    #[serde(with = "amount::string")]
+    pub refunded_amount: Amount,
 
     ///  The refund amount of this refund request.
-    pub refund_amount: String,
+    #[serde(with = "amount::string")]
+    pub refund_amount: Amount,
 
     ///  The remaining attempts of this original order. If this value becomes 1, then your next refund request amount will be ignored. We will refund all the remaing amount of this original order.
     pub remaining_attempts: u8,
@@ -47,9 +58,17 @@ pub struct Response {
     pub refund_status: RefundStatus,
 }
 
+impl Validate for Request {
+    fn check(&self, violations: &mut Violations) {
+        violations.required("refundRequestId", &self.refund_request_id);
+        violations.max_len("refundRequestId", &self.refund_request_id, 64);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::c2b::tests::test_request_serialize_deserialize;
+    use std::str::FromStr;
 
     test_request_serialize_deserialize!(
         (
@@ -65,9 +84,9 @@ mod tests {
             Response {
                 refund_request_id: "68711039982968832".to_string(),
                 prepay_id: "383729303729303".to_string(),
-                order_amount: "100.11".to_string(),
-                refunded_amount: "10.88".to_string(),
-                refund_amount: "5.00".to_string(),
+                order_amount: Amount::from_str("100.11").unwrap(),
+                refunded_amount: Amount::from_str("10.88").unwrap(),
+                refund_amount: Amount::from_str("5.00").unwrap(),
                 remaining_attempts: 8,
                 payer_open_id: "dde730c2e0ea1f1780cf26343b98fd3b".to_string(),
                 refund_status: RefundStatus::RefundSuccess,
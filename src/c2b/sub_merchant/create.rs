@@ -1,5 +1,7 @@
 //! Create Sub-merchant API used for merchant/partner.
 
+use crate::c2b::validation::{Validate, Violations};
+use crate::errors;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize)]
@@ -77,6 +79,278 @@ pub struct Response {
     pub sub_merchant_id: u64,
 }
 
+impl Validate for Request {
+    fn check(&self, violations: &mut Violations) {
+        violations.required("merchantName", &self.merchant_name);
+        violations.max_len("merchantName", &self.merchant_name, 128);
+        if !(1..=5).contains(&self.merchant_type) {
+            violations.add("merchantType", "must be between 1 and 5");
+        }
+        violations.required("merchantMcc", &self.merchant_mcc);
+        violations.required("country", &self.country);
+
+        let mut required = |field: &str, present: bool| {
+            if !present {
+                violations.add(field, "is required for this merchantType");
+            }
+        };
+        if self.merchant_type == MerchantType::Individual as u8 {
+            required("certificateType", self.certificate_type.is_some());
+            required("certificateCountry", self.certificate_country.is_some());
+            required("certificateNumber", self.certificate_number.is_some());
+            required(
+                "certificateValidDate",
+                self.certificate_valid_date.is_some(),
+            );
+        } else {
+            required("companyName", self.company_name.is_some());
+            required("registrationNumber", self.registration_number.is_some());
+            required("registrationCountry", self.registration_country.is_some());
+            required("registrationAddress", self.registration_address.is_some());
+            required("incorporationDate", self.incorporation_date.is_some());
+            required("siteType", self.site_type.is_some());
+        }
+
+        if self.site_type == Some(SiteType::Web as u8) && self.site_url.is_none() {
+            violations.add("siteUrl", "is required when siteType is Web");
+        }
+        if matches!(self.site_type, Some(1..=3)) && self.site_name.is_none() {
+            violations.add(
+                "siteName",
+                "is required when siteType is Web, App or Binance applets",
+            );
+        }
+    }
+}
+
+/// Values of `merchantType`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MerchantType {
+    Individual = 1,
+    SoloProprietor = 2,
+    Partnership = 3,
+    PrivateCompany = 4,
+    OthersCompany = 5,
+}
+
+/// Values of `storeType`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreType {
+    Online = 0,
+    Physical = 1,
+}
+
+/// Values of `siteType`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SiteType {
+    Web = 1,
+    App = 2,
+    BinanceApplets = 3,
+    Others = 4,
+}
+
+/// Values of `certificateType`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateType {
+    Id = 1,
+    Passport = 2,
+}
+
+impl Request {
+    pub fn builder() -> RequestBuilder {
+        RequestBuilder::default()
+    }
+}
+
+/// Builds a validated [`Request`].
+///
+/// The merchant name, type, MCC code and country are always required, the other required
+/// fields depend on the merchant and site types, see [`Request`].
+///
+/// ```
+/// # use bpay::api::sub_merchant::create::{CertificateType, MerchantType, Request};
+/// let request = Request::builder()
+///     .merchant_name("Individual")
+///     .merchant_type(MerchantType::Individual)
+///     .merchant_mcc("5511")
+///     .country("CN,US")
+///     .certificate(CertificateType::Passport, "US", "123456X", 1752422400000)
+///     .build()
+///     .unwrap();
+///
+/// let error = Request::builder()
+///     .merchant_name("Company")
+///     .merchant_type(MerchantType::PrivateCompany)
+///     .merchant_mcc("5511")
+///     .country("SG")
+///     .build()
+///     .unwrap_err();
+/// assert!(error.to_string().contains("companyName: is required"));
+/// ```
+#[derive(Debug, Default)]
+pub struct RequestBuilder {
+    merchant_name: Option<String>,
+    merchant_type: Option<MerchantType>,
+    merchant_mcc: Option<String>,
+    brand_logo: Option<String>,
+    country: Option<String>,
+    address: Option<String>,
+    company_name: Option<String>,
+    registration_number: Option<String>,
+    registration_country: Option<String>,
+    registration_address: Option<String>,
+    incorporation_date: Option<u64>,
+    store_type: Option<StoreType>,
+    site_type: Option<SiteType>,
+    site_url: Option<String>,
+    site_name: Option<String>,
+    certificate_type: Option<CertificateType>,
+    certificate_country: Option<String>,
+    certificate_number: Option<String>,
+    certificate_valid_date: Option<u64>,
+    contract_time_isv: Option<u64>,
+}
+
+impl RequestBuilder {
+    /// Maximum length 128, unique under one main merchant.
+    pub fn merchant_name(mut self, merchant_name: impl Into<String>) -> Self {
+        self.merchant_name = Some(merchant_name.into());
+        self
+    }
+
+    pub fn merchant_type(mut self, merchant_type: MerchantType) -> Self {
+        self.merchant_type = Some(merchant_type);
+        self
+    }
+
+    /// MCC code, get from Binance.
+    pub fn merchant_mcc(mut self, merchant_mcc: impl Into<String>) -> Self {
+        self.merchant_mcc = Some(merchant_mcc.into());
+        self
+    }
+
+    pub fn brand_logo(mut self, url: impl Into<String>) -> Self {
+        self.brand_logo = Some(url.into());
+        self
+    }
+
+    /// Iso alpha 2 country codes of the business operation split by ",", e.g. "SG,US".
+    pub fn country(mut self, country: impl Into<String>) -> Self {
+        self.country = Some(country.into());
+        self
+    }
+
+    /// Store address.
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    /// Registration of the company, required if the merchant is not an individual.
+    /// `incorporation_date` is a unix timestamp in milliseconds.
+    pub fn registration(
+        mut self,
+        company_name: impl Into<String>,
+        registration_number: impl Into<String>,
+        registration_country: impl Into<String>,
+        registration_address: impl Into<String>,
+        incorporation_date: u64,
+    ) -> Self {
+        self.company_name = Some(company_name.into());
+        self.registration_number = Some(registration_number.into());
+        self.registration_country = Some(registration_country.into());
+        self.registration_address = Some(registration_address.into());
+        self.incorporation_date = Some(incorporation_date);
+        self
+    }
+
+    pub fn store_type(mut self, store_type: StoreType) -> Self {
+        self.store_type = Some(store_type);
+        self
+    }
+
+    /// Required if the merchant is not an individual.
+    pub fn site_type(mut self, site_type: SiteType) -> Self {
+        self.site_type = Some(site_type);
+        self
+    }
+
+    /// Required if the site type is [`SiteType::Web`].
+    pub fn site_url(mut self, site_url: impl Into<String>) -> Self {
+        self.site_url = Some(site_url.into());
+        self
+    }
+
+    /// Required if the site type is Web, App or Binance applets.
+    pub fn site_name(mut self, site_name: impl Into<String>) -> Self {
+        self.site_name = Some(site_name.into());
+        self
+    }
+
+    /// Identity document, required if the merchant is an individual.
+    /// `valid_date` is a unix timestamp in milliseconds.
+    pub fn certificate(
+        mut self,
+        certificate_type: CertificateType,
+        country: impl Into<String>,
+        number: impl Into<String>,
+        valid_date: u64,
+    ) -> Self {
+        self.certificate_type = Some(certificate_type);
+        self.certificate_country = Some(country.into());
+        self.certificate_number = Some(number.into());
+        self.certificate_valid_date = Some(valid_date);
+        self
+    }
+
+    /// Contract date with ISV, unix timestamp in milliseconds.
+    pub fn contract_time_isv(mut self, contract_time_isv: u64) -> Self {
+        self.contract_time_isv = Some(contract_time_isv);
+        self
+    }
+
+    /// Fails with [`errors::Error::Validation`] if a required field is missing or the request is invalid.
+    pub fn build(self) -> errors::Result<Request> {
+        let mut violations = Violations::default();
+        let merchant_name = violations.present("merchantName", self.merchant_name);
+        let merchant_type = violations.present("merchantType", self.merchant_type);
+        let merchant_mcc = violations.present("merchantMcc", self.merchant_mcc);
+        let country = violations.present("country", self.country);
+        let (Some(merchant_name), Some(merchant_type), Some(merchant_mcc), Some(country)) =
+            (merchant_name, merchant_type, merchant_mcc, country)
+        else {
+            return Err(violations.into_error().into());
+        };
+
+        let request = Request {
+            merchant_name,
+            merchant_type: merchant_type as u8,
+            merchant_mcc,
+            brand_logo: self.brand_logo,
+            country,
+            address: self.address,
+            company_name: self.company_name,
+            registration_number: self.registration_number,
+            registration_country: self.registration_country,
+            registration_address: self.registration_address,
+            incorporation_date: self.incorporation_date,
+            store_type: self.store_type.map(|store_type| store_type as u8),
+            site_type: self.site_type.map(|site_type| site_type as u8),
+            site_url: self.site_url,
+            site_name: self.site_name,
+            certificate_type: self
+                .certificate_type
+                .map(|certificate_type| certificate_type as u8),
+            certificate_country: self.certificate_country,
+            certificate_number: self.certificate_number,
+            certificate_valid_date: self.certificate_valid_date,
+            contract_time_isv: self.contract_time_isv,
+        };
+        request.validate()?;
+        Ok(request)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::c2b::tests::test_request_serialize_deserialize;
@@ -1,5 +1,8 @@
 //! Fund transfer API used for merchant/partner to initiate Fund transfer between wallets.
 
+use crate::c2b::amount::{self, Amount};
+use crate::c2b::currency::Currency;
+use crate::c2b::validation::{Validate, Violations};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -16,10 +19,11 @@ pub struct Request {
     pub request_id: String,
 
     /// Valid currency, must be in uppercase	transfer currency, e.g. "BUSD"
-    pub currency: String,
+    pub currency: Currency,
 
     /// The transfer amount
-    pub amount: String,
+    #[serde(with = "amount::string")]
+    pub amount: Amount,
 
     /// The transfer direction specified by the merchant
     pub transfer_type: TransferType,
@@ -45,22 +49,33 @@ pub struct Response {
     /// FAILURE (indicating that the transfer has failed, it may be that the transferor has a problem with the transferee),
     /// PROCESS (the transfer is in progress)
     pub status: Status,
-    pub currency: String,
-    pub amount: String,
+    pub currency: Currency,
+    #[serde(with = "amount::string")]
+    pub amount: Amount,
     pub transfer_type: TransferType,
 }
 
+impl Validate for Request {
+    fn check(&self, violations: &mut Violations) {
+        violations.required("requestId", &self.request_id);
+        violations.max_len("requestId", &self.request_id, 32);
+        violations.currency("currency", &self.currency);
+        violations.positive("amount", &self.amount);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::c2b::tests::test_request_serialize_deserialize;
+    use std::str::FromStr;
     test_request_serialize_deserialize!(
         (
             test_transfer_fund_serialize,
             r#"{"requestId":"100002021071407140001","currency":"BNB","amount":"0.01","transferType":"TO_MAIN"}"#,
             Request {
                 request_id: "100002021071407140001".to_string(),
-                currency: "BNB".to_string(),
-                amount: "0.01".to_string(),
+                currency: Currency::BNB,
+                amount: Amount::from_str("0.01").unwrap(),
                 transfer_type: TransferType::ToMain,
             }
         ),
@@ -70,8 +85,8 @@ mod tests {
             Response {
                 tran_id: "100002021071407140001".to_string(),
                 status: Status::Success,
-                currency: "BNB".to_string(),
-                amount: "0.01".to_string(),
+                currency: Currency::BNB,
+                amount: Amount::from_str("0.01").unwrap(),
                 transfer_type: TransferType::ToMain,
             }
         )
@@ -1,5 +1,6 @@
 //! Query Transfer Result API used for merchant/partner to query transfer result.
 
+use crate::c2b::validation::{Validate, Violations};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Debug)]
@@ -8,7 +9,7 @@ pub struct Request {
     pub tran_id: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(test, derive(Serialize))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Status {
@@ -17,6 +18,12 @@ pub enum Status {
     Process,
 }
 
+impl Status {
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, Status::Process)
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[cfg_attr(test, derive(Serialize))]
 pub struct Response {
@@ -26,6 +33,12 @@ pub struct Response {
     pub status: Status,
 }
 
+impl Validate for Request {
+    fn check(&self, violations: &mut Violations) {
+        violations.required("tranId", &self.tran_id);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::c2b::tests::test_request_serialize_deserialize;
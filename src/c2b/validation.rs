@@ -0,0 +1,181 @@
+//! Client side checks of the documented request limits.
+/*!
+Every request implements [`Validate`], the action methods, e.g. `.create()` or `.query()`,
+run it before signing so an invalid request never reaches the network.
+The violations are reported with the JSON name of the field.
+
+```
+# use bpay::api::order::query::Request;
+# use bpay::c2b::validation::Validate;
+let error = Request::new(None, None).validate().unwrap_err();
+assert_eq!(error.violations[0].field, "prepayId");
+```
+*/
+
+use crate::c2b::amount::{Amount, PRECISION};
+use crate::c2b::currency::Currency;
+use std::fmt;
+use thiserror::Error;
+
+/// A field which doesn't respect the documented limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// JSON path of the field, e.g. `goods.goodsName`.
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// All the violations found in a request.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub struct ValidationError {
+    pub violations: Vec<Violation>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid request")?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            f.write_str(if i == 0 { ": " } else { ", " })?;
+            write!(f, "{violation}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects the violations while checking a request.
+#[derive(Debug, Default)]
+pub struct Violations(Vec<Violation>);
+
+impl Violations {
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.push(Violation {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    /// The builder field must be set, the value is passed through.
+    pub fn present<T>(&mut self, field: &str, value: Option<T>) -> Option<T> {
+        if value.is_none() {
+            self.add(field, "is required");
+        }
+        value
+    }
+
+    /// The value must not be empty.
+    pub fn required(&mut self, field: &str, value: &str) {
+        if value.is_empty() {
+            self.add(field, "must not be empty");
+        }
+    }
+
+    /// The value must not be longer than `max` characters.
+    pub fn max_len(&mut self, field: &str, value: &str, max: usize) {
+        if value.chars().count() > max {
+            self.add(field, format!("must be at most {max} characters long"));
+        }
+    }
+
+    /// The value must be made of 1 to `max` letters or digits.
+    pub fn alphanumeric(&mut self, field: &str, value: &str, max: usize) {
+        if self.letters_and_digits(field, value) {
+            self.max_len(field, value, max);
+        }
+    }
+
+    /// The prepay id must be made of letters or digits, of any length as the ids Binance
+    /// returns are longer than the documented 19 characters.
+    pub fn prepay_id(&mut self, value: &str) {
+        self.letters_and_digits("prepayId", value);
+    }
+
+    /// Either id of an existing order must be present, the ones given must be valid.
+    pub fn order_id(&mut self, prepay_id: Option<&str>, merchant_trade_no: Option<&str>) {
+        if prepay_id.is_none() && merchant_trade_no.is_none() {
+            self.add(
+                "prepayId",
+                "either prepayId or merchantTradeNo must be present",
+            );
+        }
+        if let Some(prepay_id) = prepay_id {
+            self.prepay_id(prepay_id);
+        }
+        if let Some(merchant_trade_no) = merchant_trade_no {
+            self.alphanumeric("merchantTradeNo", merchant_trade_no, 32);
+        }
+    }
+
+    fn letters_and_digits(&mut self, field: &str, value: &str) -> bool {
+        if value.is_empty() {
+            self.add(field, "must not be empty");
+        } else if !value.chars().all(|c| c.is_ascii_alphanumeric()) {
+            self.add(field, "must only contain letters and digits");
+        } else {
+            return true;
+        }
+        false
+    }
+
+    /// The amount must be within `min` and `max`, both included, see [`Violations::precision`].
+    pub fn range(&mut self, field: &str, amount: &Amount, min: &Amount, max: &Amount) {
+        if amount < min || amount > max {
+            self.add(field, format!("must be between {min} and {max}"));
+        }
+        self.precision(field, amount);
+    }
+
+    /// The amount must be greater than zero, see [`Violations::precision`].
+    pub fn positive(&mut self, field: &str, amount: &Amount) {
+        if *amount <= Amount::zero() {
+            self.add(field, "must be greater than 0");
+        }
+        self.precision(field, amount);
+    }
+
+    /// The amount must not have more than [`PRECISION`] decimal places.
+    pub fn precision(&mut self, field: &str, amount: &Amount) {
+        if amount.decimal_places() > PRECISION {
+            self.add(
+                field,
+                format!("must have at most {PRECISION} decimal places"),
+            );
+        }
+    }
+
+    /// The currency code must be in uppercase.
+    pub fn currency(&mut self, field: &str, currency: &Currency) {
+        if !currency.is_valid() {
+            self.add(field, "must be an uppercase currency code");
+        }
+    }
+
+    pub fn into_result(self) -> Result<(), ValidationError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.into_error())
+        }
+    }
+
+    pub fn into_error(self) -> ValidationError {
+        ValidationError { violations: self.0 }
+    }
+}
+
+/// Implemented by every request.
+pub trait Validate {
+    /// Adds the violations found in `self` to `violations`.
+    fn check(&self, violations: &mut Violations);
+
+    fn validate(&self) -> Result<(), ValidationError> {
+        let mut violations = Violations::default();
+        self.check(&mut violations);
+        violations.into_result()
+    }
+}
@@ -1,5 +1,8 @@
 //! API used to query wallet balance.
 
+use crate::c2b::amount::Amount;
+use crate::c2b::currency::Currency;
+use crate::c2b::validation::{Validate, Violations};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -16,7 +19,7 @@ pub struct Request {
     pub wallet: WalletType,
 
     /// Valid currency, must be in uppercase, e.g, "BUSD".
-    pub currency: String,
+    pub currency: Currency,
 }
 
 #[derive(Deserialize, Debug)]
@@ -24,25 +27,32 @@ pub struct Request {
 #[serde(rename_all = "camelCase")]
 pub struct Response {
     /// Current balance free to use
-    pub balance: f64,
+    pub balance: Amount,
 
     /// Asset/currency name
-    pub asset: String,
+    pub asset: Currency,
 
     /// Fiat name, for fiat valuation
     pub fiat: String,
 
     /// Available fiat valuation
-    pub available_fiat_valuation: f64,
+    pub available_fiat_valuation: Amount,
 
     /// Available BTC valuation
-    pub available_btc_valuation: f64,
+    pub available_btc_valuation: Amount,
+}
+
+impl Validate for Request {
+    fn check(&self, violations: &mut Violations) {
+        violations.currency("currency", &self.currency);
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use crate::c2b::tests::test_request_serialize_deserialize;
+    use std::str::FromStr;
 
     test_request_serialize_deserialize!(
         (
@@ -50,7 +60,7 @@ mod tests {
             r#"{"wallet":"SPOT_WALLET","currency":"BUSD"}"#,
             Request {
                 wallet: WalletType::SpotWallet,
-                currency: "BUSD".to_string(),
+                currency: Currency::BUSD,
             }
         ),
         (
@@ -65,11 +75,11 @@ mod tests {
             }
             "#,
             Response {
-                balance: 990000.0,
-                asset: "BUSD".to_string(),
+                balance: Amount::from_str("990000.00000000").unwrap(),
+                asset: Currency::BUSD,
                 fiat: "USD".to_string(),
-                available_fiat_valuation: 989991.90516600,
-                available_btc_valuation: 22.9878,
+                available_fiat_valuation: Amount::from_str("989991.905166").unwrap(),
+                available_btc_valuation: Amount::from_str("22.9878").unwrap(),
             }
         )
     );
@@ -0,0 +1,535 @@
+//! Handles each notification once, although Binance sends it until it gets a success reply.
+/*!
+[`Idempotent`] keys the notifications on their `bizType`, `bizId` and `bizStatus` and records
+in a [`DedupStore`] whether they are being processed, processed or failed. A notification
+already processed is acknowledged without running the handler again, one still being processed
+is refused so Binance retries it later, and a failed one is processed again.
+
+```
+# use bpay::c2b::webhook::dedup::{Idempotent, MemoryDedupStore, Outcome};
+# use bpay::c2b::webhook::router::NotificationRouter;
+# #[tokio::main]
+# async fn main() -> bpay::errors::Result<()> {
+# let body = r#"{"bizType":"PAYOUT","data":"{\"batchStatus\":\"SUCCESS\",\"currency\":\"BUSD\",\"merchantId\":100100006288,\"requestId\":\"gg8127129\",\"totalAmount\":2.00000000,\"totalNumber\":2}","bizId":29383937493038367292,"bizStatus":"SUCCESS"}"#;
+let router = NotificationRouter::new().on_payout(|event| async move {
+    println!("{} paid out", event.detail.request_id);
+    Ok(())
+});
+let idempotent = Idempotent::new(MemoryDedupStore::new());
+assert!(matches!(idempotent.dispatch(body, &router).await?, Outcome::Processed));
+assert!(matches!(idempotent.dispatch(body, &router).await?, Outcome::Duplicate(_)));
+# Ok(())
+# }
+```
+*/
+
+use super::notification::{Notification, NotificationRequestParams};
+use super::router::{Acknowledgement, HandlerError, NotificationRouter};
+use crate::errors::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+#[cfg(any(test, feature = "sqlite"))]
+use crate::errors::Error;
+#[cfg(any(test, feature = "sqlite"))]
+use std::sync::Arc;
+
+/// Identifies a notification and its retries.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DedupKey {
+    pub biz_type: String,
+    pub biz_id: u128,
+    pub biz_status: String,
+}
+
+impl From<&NotificationRequestParams> for DedupKey {
+    fn from(params: &NotificationRequestParams) -> Self {
+        Self {
+            biz_type: params.biz_type.as_str().into(),
+            biz_id: params.biz_id,
+            biz_status: match &params.biz_status {
+                Value::String(status) => status.clone(),
+                status => status.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessingState {
+    InFlight,
+    Done,
+    Failed,
+}
+
+#[cfg(any(test, feature = "sqlite"))]
+impl ProcessingState {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ProcessingState::InFlight => "IN_FLIGHT",
+            ProcessingState::Done => "DONE",
+            ProcessingState::Failed => "FAILED",
+        }
+    }
+
+    fn from_str(state: &str) -> Option<Self> {
+        match state {
+            "IN_FLIGHT" => Some(ProcessingState::InFlight),
+            "DONE" => Some(ProcessingState::Done),
+            "FAILED" => Some(ProcessingState::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Result of [`DedupStore::begin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Begin {
+    /// The notification is now marked in flight for the caller, with its previous state if
+    /// it failed or was abandoned in flight.
+    Acquired(Option<ProcessingState>),
+
+    /// The notification is done or in flight since less than `stale_after`, left unchanged.
+    Busy(ProcessingState),
+}
+
+/// Records the processing state of the notifications.
+#[async_trait::async_trait]
+pub trait DedupStore: Send + Sync {
+    /// Marks the notification in flight unless it is done or in flight since less than
+    /// `stale_after`.
+    async fn begin(&self, key: &DedupKey, stale_after: Duration) -> Result<Begin>;
+
+    /// Records the state the processing ended with.
+    async fn finish(&self, key: &DedupKey, state: ProcessingState) -> Result<()>;
+}
+
+/// Whether `begin` may take the notification over.
+fn begin(previous: Option<(ProcessingState, SystemTime)>, stale_after: Duration) -> Begin {
+    match previous {
+        Some((ProcessingState::Done, _)) => Begin::Busy(ProcessingState::Done),
+        Some((ProcessingState::InFlight, since))
+            if since.elapsed().unwrap_or_default() < stale_after =>
+        {
+            Begin::Busy(ProcessingState::InFlight)
+        }
+        previous => Begin::Acquired(previous.map(|(state, _)| state)),
+    }
+}
+
+/// States are kept for a day, Binance stops retrying a notification well before.
+const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Processing states kept in memory, they are lost on restart.
+#[derive(Debug)]
+pub struct MemoryDedupStore {
+    states: Mutex<HashMap<DedupKey, (ProcessingState, SystemTime)>>,
+    ttl: Duration,
+}
+
+impl Default for MemoryDedupStore {
+    fn default() -> Self {
+        Self {
+            states: Mutex::default(),
+            ttl: DEFAULT_TTL,
+        }
+    }
+}
+
+impl MemoryDedupStore {
+    /// Keeps the states for 24 hours after their last update.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time after its last update a state is forgotten, it must outlast the retries of Binance.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl DedupStore for MemoryDedupStore {
+    async fn begin(&self, key: &DedupKey, stale_after: Duration) -> Result<Begin> {
+        let mut states = self.states.lock().unwrap();
+        states.retain(|_, (_, since)| since.elapsed().unwrap_or_default() < self.ttl);
+        let begin = begin(states.get(key).copied(), stale_after);
+        if let Begin::Acquired(_) = begin {
+            states.insert(key.clone(), (ProcessingState::InFlight, SystemTime::now()));
+        }
+        Ok(begin)
+    }
+
+    async fn finish(&self, key: &DedupKey, state: ProcessingState) -> Result<()> {
+        self.states
+            .lock()
+            .unwrap()
+            .insert(key.clone(), (state, SystemTime::now()));
+        Ok(())
+    }
+}
+
+/// Processing states kept in a SQLite database, shared by the processes using the same file.
+/// The queries run on the blocking thread pool of tokio.
+#[cfg(any(test, feature = "sqlite"))]
+pub struct SqliteDedupStore {
+    connection: Arc<Mutex<rusqlite::Connection>>,
+    ttl: Duration,
+}
+
+#[cfg(any(test, feature = "sqlite"))]
+impl SqliteDedupStore {
+    /// Opens the database and creates the `bpay_notifications` table if needed.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        Self::from_connection(rusqlite::Connection::open(path)?)
+    }
+
+    pub fn from_connection(connection: rusqlite::Connection) -> Result<Self> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS bpay_notifications (
+                biz_type TEXT NOT NULL,
+                biz_id TEXT NOT NULL,
+                biz_status TEXT NOT NULL,
+                state TEXT NOT NULL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (biz_type, biz_id, biz_status)
+            )",
+        )?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            ttl: DEFAULT_TTL,
+        })
+    }
+
+    /// Time after its last update a state is deleted, 24 hours by default, see
+    /// [`MemoryDedupStore::ttl`].
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    async fn with_connection<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut rusqlite::Connection) -> Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || f(&mut connection.lock().unwrap()))
+            .await
+            .map_err(|e| Error::Msg(format!("SQLite task failed: {e}")))?
+    }
+
+    fn store(
+        connection: &rusqlite::Connection,
+        key: &DedupKey,
+        state: ProcessingState,
+    ) -> Result<()> {
+        connection.execute(
+            "INSERT OR REPLACE INTO bpay_notifications VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                key.biz_type,
+                key.biz_id.to_string(),
+                key.biz_status,
+                state.as_str(),
+                millis(SystemTime::now())
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(any(test, feature = "sqlite"))]
+fn millis(time: SystemTime) -> i64 {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(any(test, feature = "sqlite"))]
+#[async_trait::async_trait]
+impl DedupStore for SqliteDedupStore {
+    async fn begin(&self, key: &DedupKey, stale_after: Duration) -> Result<Begin> {
+        use rusqlite::OptionalExtension;
+
+        let (key, ttl) = (key.clone(), self.ttl);
+        self.with_connection(move |connection| {
+            let transaction =
+                connection.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+            let expired = SystemTime::now()
+                .checked_sub(ttl)
+                .unwrap_or(std::time::UNIX_EPOCH);
+            transaction.execute(
+                "DELETE FROM bpay_notifications WHERE updated_at <= ?1",
+                [millis(expired)],
+            )?;
+            let previous = transaction
+                .query_row(
+                    "SELECT state, updated_at FROM bpay_notifications
+                     WHERE biz_type = ?1 AND biz_id = ?2 AND biz_status = ?3",
+                    rusqlite::params![key.biz_type, key.biz_id.to_string(), key.biz_status],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+                )
+                .optional()?
+                .and_then(|(state, updated_at)| {
+                    let since =
+                        std::time::UNIX_EPOCH + Duration::from_millis(updated_at.max(0) as u64);
+                    Some((ProcessingState::from_str(&state)?, since))
+                });
+            let begin = begin(previous, stale_after);
+            if let Begin::Acquired(_) = begin {
+                Self::store(&transaction, &key, ProcessingState::InFlight)?;
+            }
+            transaction.commit()?;
+            Ok(begin)
+        })
+        .await
+    }
+
+    async fn finish(&self, key: &DedupKey, state: ProcessingState) -> Result<()> {
+        let key = key.clone();
+        self.with_connection(move |connection| Self::store(connection, &key, state))
+            .await
+    }
+}
+
+/// Result of [`Idempotent::handle`].
+#[derive(Debug)]
+pub enum Outcome {
+    /// The handler ran and succeeded.
+    Processed,
+
+    /// The handler ran and failed, the notification will be processed again.
+    Failed(HandlerError),
+
+    /// The handler didn't run, the notification is done or still in flight.
+    Duplicate(ProcessingState),
+}
+
+impl Outcome {
+    /// `SUCCESS` once the notification is processed, otherwise `FAIL` so Binance sends it again.
+    pub fn acknowledgement(&self) -> Acknowledgement {
+        match self {
+            Outcome::Processed | Outcome::Duplicate(ProcessingState::Done) => {
+                Acknowledgement::success()
+            }
+            Outcome::Failed(e) => Acknowledgement::fail(e.to_string()),
+            Outcome::Duplicate(_) => Acknowledgement::fail("already being processed"),
+        }
+    }
+}
+
+/// Runs the handler at most once per notification, retries excepted.
+pub struct Idempotent<S> {
+    store: S,
+    stale_after: Duration,
+}
+
+impl<S: DedupStore> Idempotent<S> {
+    /// Takes over the notifications in flight for more than 5 minutes.
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            stale_after: Duration::from_secs(5 * 60),
+        }
+    }
+
+    /// Time after which a notification still in flight is considered abandoned, e.g. by a
+    /// crashed process, and processed again.
+    pub fn stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Parses the verified body and runs the handler unless the notification is a duplicate.
+    pub async fn handle<F, Fut>(&self, body: &str, handler: F) -> Result<Outcome>
+    where
+        F: FnOnce(Notification) -> Fut,
+        Fut: Future<Output = std::result::Result<(), HandlerError>>,
+    {
+        let params = NotificationRequestParams::try_from(body)?;
+        let key = DedupKey::from(&params);
+        let notification = Notification::try_from(params)?;
+        if let Begin::Busy(state) = self.store.begin(&key, self.stale_after).await? {
+            return Ok(Outcome::Duplicate(state));
+        }
+        match handler(notification).await {
+            Ok(()) => {
+                self.store.finish(&key, ProcessingState::Done).await?;
+                Ok(Outcome::Processed)
+            }
+            Err(e) => {
+                self.store.finish(&key, ProcessingState::Failed).await?;
+                Ok(Outcome::Failed(e))
+            }
+        }
+    }
+
+    /// Dispatches the notification to the router unless it is a duplicate.
+    pub async fn dispatch(&self, body: &str, router: &NotificationRouter) -> Result<Outcome> {
+        self.handle(body, |notification| async move {
+            let dispatched = router.dispatch(notification).await;
+            if dispatched.is_success() {
+                Ok(())
+            } else {
+                Err(dispatched
+                    .acknowledgement()
+                    .return_message
+                    .unwrap_or_default()
+                    .into())
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYOUT: &str = r#"{"bizType":"PAYOUT","data":"{\"batchStatus\":\"SUCCESS\",\"currency\":\"BUSD\",\"merchantId\":100100006288,\"requestId\":\"gg8127129\",\"totalAmount\":2.00000000,\"totalNumber\":2}","bizId":29383937493038367292,"bizStatus":"SUCCESS"}"#;
+
+    async fn check_store(store: impl DedupStore) {
+        let idempotent = Idempotent::new(store);
+        let outcome = idempotent
+            .handle(PAYOUT, |_| async { Err("database down".into()) })
+            .await
+            .unwrap();
+        assert!(matches!(outcome, Outcome::Failed(_)));
+        assert!(!outcome.acknowledgement().is_success());
+
+        let outcome = idempotent
+            .handle(PAYOUT, |_| async { Ok(()) })
+            .await
+            .unwrap();
+        assert!(matches!(outcome, Outcome::Processed));
+
+        let outcome = idempotent
+            .handle(PAYOUT, |_| async { panic!("processed twice") })
+            .await
+            .unwrap();
+        assert!(matches!(outcome, Outcome::Duplicate(ProcessingState::Done)));
+        assert!(outcome.acknowledgement().is_success());
+
+        let key = DedupKey {
+            biz_type: "PAYOUT".into(),
+            biz_id: 1,
+            biz_status: "SUCCESS".into(),
+        };
+        let store = &idempotent.store;
+        assert_eq!(
+            store.begin(&key, Duration::from_secs(60)).await.unwrap(),
+            Begin::Acquired(None)
+        );
+        assert_eq!(
+            store.begin(&key, Duration::from_secs(60)).await.unwrap(),
+            Begin::Busy(ProcessingState::InFlight)
+        );
+        // Abandoned in flight notifications are taken over.
+        assert_eq!(
+            store.begin(&key, Duration::ZERO).await.unwrap(),
+            Begin::Acquired(Some(ProcessingState::InFlight))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        check_store(MemoryDedupStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        check_store(
+            SqliteDedupStore::from_connection(rusqlite::Connection::open_in_memory().unwrap())
+                .unwrap(),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_duplicates_are_refused() {
+        let idempotent = Idempotent::new(MemoryDedupStore::new());
+        let key = DedupKey::from(&NotificationRequestParams::try_from(PAYOUT).unwrap());
+        assert_eq!(key.biz_id, 29383937493038367292);
+        idempotent
+            .store
+            .begin(&key, idempotent.stale_after)
+            .await
+            .unwrap();
+        let outcome = idempotent
+            .handle(PAYOUT, |_| async { panic!("processed twice") })
+            .await
+            .unwrap();
+        assert!(matches!(
+            outcome,
+            Outcome::Duplicate(ProcessingState::InFlight)
+        ));
+        assert!(!outcome.acknowledgement().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_notifications_are_processed_again() {
+        let idempotent = Idempotent::new(MemoryDedupStore::new()).stale_after(Duration::ZERO);
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let handler = |_| {
+            calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            std::future::pending::<std::result::Result<(), HandlerError>>()
+        };
+        // The process crashes while handling the notification, which stays in flight.
+        let crashed = tokio::time::timeout(
+            Duration::from_millis(10),
+            idempotent.handle(PAYOUT, handler),
+        )
+        .await;
+        assert!(crashed.is_err());
+
+        let outcome = idempotent
+            .handle(PAYOUT, |_| async {
+                calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(())
+            })
+            .await
+            .unwrap();
+        assert!(matches!(outcome, Outcome::Processed));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_forgets_expired_states() {
+        let store = MemoryDedupStore::new().ttl(Duration::ZERO);
+        let key = DedupKey::from(&NotificationRequestParams::try_from(PAYOUT).unwrap());
+        store.finish(&key, ProcessingState::Done).await.unwrap();
+        assert_eq!(
+            store.begin(&key, Duration::from_secs(60)).await.unwrap(),
+            Begin::Acquired(None)
+        );
+        assert_eq!(store.states.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_deletes_expired_states() {
+        let store =
+            SqliteDedupStore::from_connection(rusqlite::Connection::open_in_memory().unwrap())
+                .unwrap()
+                .ttl(Duration::ZERO);
+        let key = DedupKey::from(&NotificationRequestParams::try_from(PAYOUT).unwrap());
+        store.finish(&key, ProcessingState::Done).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        assert_eq!(
+            store.begin(&key, Duration::from_secs(60)).await.unwrap(),
+            Begin::Acquired(None)
+        );
+        let rows: i64 = store
+            .connection
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM bpay_notifications", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+}
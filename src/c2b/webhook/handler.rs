@@ -0,0 +1,439 @@
+//! Verified notifications for `axum` and any `tower` based server, e.g. `hyper`.
+/*!
+[`VerifiedNotification`] extracts the notification of a request once its signature is verified
+with the [`SharedVerifier`] added as an extension. [`NotificationLayer`] does the same for a
+`tower` service of `Request<Notification>`. Both reject with the status of the failure and reply
+in the format Binance expects, `{"returnCode":"SUCCESS","returnMessage":null}` once handled.
+Bodies larger than [`MAX_BODY_SIZE`] are rejected with `413` before being read entirely.
+
+```rust,no_run
+# use axum::routing::post;
+# use axum::{Extension, Router};
+# use bpay::c2b::webhook::handler::{NotificationAck, SharedVerifier, VerifiedNotification};
+# use bpay::c2b::webhook::verification::Verifier;
+# use bpay::client::Client;
+# use std::sync::Arc;
+async fn notification(VerifiedNotification(notification): VerifiedNotification) -> NotificationAck {
+    println!("{notification:?}");
+    NotificationAck
+}
+
+# #[tokio::main]
+# async fn main() -> bpay::errors::Result<()> {
+let verifier = Verifier::from_api(&Client::from_env()).await?;
+let app = Router::new()
+    .route("/", post(notification))
+    .layer(Extension::<SharedVerifier>(Arc::new(verifier)));
+# axum::Server::bind(&([127, 0, 0, 1], 3000).into())
+#     .serve(app.into_make_service())
+#     .await
+#     .unwrap();
+# Ok(())
+# }
+```
+*/
+
+use super::notification::Notification;
+use super::rotation::RotatingVerifier;
+use super::router::Acknowledgement;
+use super::verification::{Verifier, Verify};
+use crate::errors::{Error, Result};
+use axum::body::{Bytes, Full, HttpBody};
+use axum::extract::rejection::{BytesRejection, FailedToBufferBody};
+use axum::extract::{FromRequest, RequestParts};
+use axum::http::{HeaderMap, Request, Response, StatusCode};
+use axum::response::IntoResponse;
+use axum::BoxError;
+use http_body::Limited;
+use std::convert::Infallible;
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use thiserror::Error;
+use tower::{Layer, Service};
+
+/// Largest notification body accepted, 64 KiB.
+pub const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Verifies the notification requests, implemented by [`Verifier`] and [`RotatingVerifier`].
+#[async_trait::async_trait]
+pub trait NotificationVerifier: Send + Sync + 'static {
+    async fn verify_notification(&self, headers: &HeaderMap, body: &[u8]) -> Result<()>;
+}
+
+#[async_trait::async_trait]
+impl NotificationVerifier for Verifier {
+    async fn verify_notification(&self, headers: &HeaderMap, body: &[u8]) -> Result<()> {
+        Ok(self.verify(headers, body)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationVerifier for RotatingVerifier {
+    async fn verify_notification(&self, headers: &HeaderMap, body: &[u8]) -> Result<()> {
+        self.verify(headers, body).await
+    }
+}
+
+/// Verifier shared by the handlers, looked up by [`VerifiedNotification`] in the extensions.
+pub type SharedVerifier = Arc<dyn NotificationVerifier>;
+
+/// Why a notification request was rejected.
+#[derive(Error, Debug)]
+pub enum NotificationRejection {
+    #[error("no SharedVerifier extension")]
+    MissingVerifier,
+
+    #[error("could not read the body: {0}")]
+    Body(String),
+
+    #[error("the body is larger than {MAX_BODY_SIZE} bytes")]
+    TooLarge,
+
+    #[error(transparent)]
+    Verification(Error),
+
+    #[error("invalid notification: {0}")]
+    Parse(Error),
+}
+
+impl NotificationRejection {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            NotificationRejection::Body(_) | NotificationRejection::Parse(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            NotificationRejection::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            NotificationRejection::Verification(Error::Verification(e)) => {
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::UNAUTHORIZED)
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for NotificationRejection {
+    fn into_response(self) -> axum::response::Response {
+        log::warn!("Rejected the notification: {self}");
+        reply(self.status(), Acknowledgement::fail(self.to_string())).into_response()
+    }
+}
+
+/// Tells Binance the notification was handled.
+#[derive(Debug, Clone, Copy)]
+pub struct NotificationAck;
+
+impl IntoResponse for NotificationAck {
+    fn into_response(self) -> axum::response::Response {
+        Acknowledgement::success().into_response()
+    }
+}
+
+/// `200` for a success, otherwise `500` so Binance sends the notification again.
+impl IntoResponse for Acknowledgement {
+    fn into_response(self) -> axum::response::Response {
+        let status = if self.is_success() {
+            StatusCode::OK
+        } else {
+            StatusCode::INTERNAL_SERVER_ERROR
+        };
+        reply(status, self).into_response()
+    }
+}
+
+fn reply(status: StatusCode, acknowledgement: Acknowledgement) -> Response<Full<Bytes>> {
+    let body = serde_json::to_string(&acknowledgement).unwrap();
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Full::from(body))
+        .unwrap()
+}
+
+/// Notification of a request whose signature was verified.
+#[derive(Debug)]
+pub struct VerifiedNotification(pub Notification);
+
+#[async_trait::async_trait]
+impl<B> FromRequest<B> for VerifiedNotification
+where
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = NotificationRejection;
+
+    async fn from_request(req: &mut RequestParts<B>) -> std::result::Result<Self, Self::Rejection> {
+        let verifier = req
+            .extensions()
+            .get::<SharedVerifier>()
+            .cloned()
+            .ok_or(NotificationRejection::MissingVerifier)?;
+        let body = req
+            .take_body()
+            .ok_or_else(|| NotificationRejection::Body("already extracted".into()))?;
+        let body = read_body(body).await?;
+        verified(&*verifier, req.headers(), &body)
+            .await
+            .map(VerifiedNotification)
+    }
+}
+
+/// Reads at most [`MAX_BODY_SIZE`] bytes, the signature can't be checked before the whole body
+/// is read.
+async fn read_body<B>(body: B) -> std::result::Result<Bytes, NotificationRejection>
+where
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    let body = Limited::new(body, MAX_BODY_SIZE);
+    Bytes::from_request(&mut RequestParts::new(Request::new(body)))
+        .await
+        .map_err(|e| match e {
+            BytesRejection::FailedToBufferBody(FailedToBufferBody::LengthLimitError(_)) => {
+                NotificationRejection::TooLarge
+            }
+            e => NotificationRejection::Body(e.to_string()),
+        })
+}
+
+async fn verified(
+    verifier: &dyn NotificationVerifier,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> std::result::Result<Notification, NotificationRejection> {
+    verifier
+        .verify_notification(headers, body)
+        .await
+        .map_err(NotificationRejection::Verification)?;
+    let body =
+        std::str::from_utf8(body).map_err(|e| NotificationRejection::Parse(Error::UTF8Err(e)))?;
+    Notification::try_from(body).map_err(NotificationRejection::Parse)
+}
+
+/// Verifies and parses the requests before passing them to a service of `Request<Notification>`.
+/// The service replies with a [`NotificationAck`] once the inner one succeeds.
+#[derive(Clone)]
+pub struct NotificationLayer {
+    verifier: SharedVerifier,
+}
+
+impl NotificationLayer {
+    pub fn new(verifier: impl NotificationVerifier) -> Self {
+        Self {
+            verifier: Arc::new(verifier),
+        }
+    }
+}
+
+impl<S> Layer<S> for NotificationLayer {
+    type Service = NotificationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        NotificationService {
+            inner,
+            verifier: self.verifier.clone(),
+        }
+    }
+}
+
+/// Service built by [`NotificationLayer`].
+#[derive(Clone)]
+pub struct NotificationService<S> {
+    inner: S,
+    verifier: SharedVerifier,
+}
+
+impl<S, B> Service<Request<B>> for NotificationService<S>
+where
+    S: Service<Request<Notification>> + Clone + Send + 'static,
+    S::Error: Display + Send,
+    S::Future: Send,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Response = Response<Full<Bytes>>;
+    type Error = Infallible;
+    type Future =
+        Pin<Box<dyn Future<Output = std::result::Result<Self::Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Infallible>> {
+        // The inner service is driven to readiness by the call, once the request is verified.
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let verifier = self.verifier.clone();
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let notification = match read_body(body).await {
+                Ok(body) => verified(&*verifier, &parts.headers, &body).await,
+                Err(rejection) => Err(rejection),
+            };
+            let notification = match notification {
+                Ok(notification) => notification,
+                Err(rejection) => {
+                    log::warn!("Rejected the notification: {rejection}");
+                    let acknowledgement = Acknowledgement::fail(rejection.to_string());
+                    return Ok(reply(rejection.status(), acknowledgement));
+                }
+            };
+            let handled = match std::future::poll_fn(|cx| inner.poll_ready(cx)).await {
+                Ok(()) => inner
+                    .call(Request::from_parts(parts, notification))
+                    .await
+                    .map(|_| ()),
+                Err(e) => Err(e),
+            };
+            Ok(match handled {
+                Ok(()) => reply(StatusCode::OK, Acknowledgement::success()),
+                Err(e) => {
+                    log::error!("Could not handle the notification: {e}");
+                    let acknowledgement = Acknowledgement::fail(e.to_string());
+                    reply(StatusCode::INTERNAL_SERVER_ERROR, acknowledgement)
+                }
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::KeyPair;
+    use axum::body::Body;
+    use axum::routing::post;
+    use axum::{Extension, Router};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    const BODY: &str = r#"{"bizType":"PAY","data":"{\"merchantTradeNo\":\"9825382937292\",\"totalFee\":0.88000000,\"transactTime\":1619508939664,\"currency\":\"USDT\",\"openUserId\":\"1211HS10K81f4273ac031\",\"productType\":\"Food\",\"productName\":\"Ice Cream\",\"tradeType\":\"WEB\",\"transactionId\":\"M_R_282737362839373\"}","bizId":29383937493038367292,"bizStatus":"PAY_SUCCESS"}"#;
+
+    fn verifier() -> Verifier {
+        KeyPair::bundled()
+            .unwrap()
+            .signer()
+            .unwrap()
+            .verifier()
+            .unwrap()
+    }
+
+    fn request(body: &str, signed_body: &str) -> Request<Body> {
+        let signed = KeyPair::bundled()
+            .unwrap()
+            .signer()
+            .unwrap()
+            .sign_with("1646584911979", "nonce", signed_body)
+            .unwrap();
+        let mut request = Request::post("/");
+        for (key, val) in signed.headers {
+            request = request.header(key, val);
+        }
+        request.body(Body::from(body.to_string())).unwrap()
+    }
+
+    async fn send<S>(service: &mut S, request: Request<Body>) -> (StatusCode, serde_json::Value)
+    where
+        S: Service<Request<Body>>,
+        S::Response: IntoResponse,
+        S::Error: std::fmt::Debug,
+    {
+        std::future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .unwrap();
+        let response = service.call(request).await.unwrap().into_response();
+        let status = response.status();
+        let body = hyper_body(response.into_body()).await;
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn hyper_body<B>(body: B) -> Bytes
+    where
+        B: HttpBody + Send,
+        B::Data: Send,
+        B::Error: Into<BoxError>,
+    {
+        Bytes::from_request(&mut RequestParts::new(Request::new(body)))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_extractor() {
+        async fn handler(
+            VerifiedNotification(notification): VerifiedNotification,
+        ) -> NotificationAck {
+            assert!(matches!(notification, Notification::Order { .. }));
+            NotificationAck
+        }
+        let mut app = Router::new()
+            .route("/", post(handler))
+            .layer(Extension::<SharedVerifier>(Arc::new(verifier())));
+
+        let (status, body) = send(&mut app, request(BODY, BODY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            json!({"returnCode": "SUCCESS", "returnMessage": null})
+        );
+
+        let (status, body) = send(&mut app, request("{}", BODY)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["returnCode"], "FAIL");
+
+        let (status, _) = send(&mut app, request("{}", "{}")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let large = " ".repeat(MAX_BODY_SIZE + 1);
+        let (status, body) = send(&mut app, request(&large, &large)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["returnCode"], "FAIL");
+    }
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<u128>>>);
+
+    impl Service<Request<Notification>> for Recorder {
+        type Response = ();
+        type Error = Infallible;
+        type Future = std::future::Ready<std::result::Result<(), Infallible>>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<Notification>) -> Self::Future {
+            if let Notification::Order { biz_id, .. } = request.into_body() {
+                self.0.lock().unwrap().push(biz_id);
+            }
+            std::future::ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_layer() {
+        let recorder = Recorder::default();
+        let mut service = NotificationLayer::new(verifier()).layer(recorder.clone());
+
+        let (status, body) = send(&mut service, request(BODY, BODY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["returnCode"], "SUCCESS");
+
+        let (status, _) = send(&mut service, request(BODY, "{}")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let large = format!("{BODY}{}", " ".repeat(MAX_BODY_SIZE));
+        let (status, _) = send(&mut service, request(&large, &large)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(*recorder.0.lock().unwrap(), [29383937493038367292]);
+    }
+}
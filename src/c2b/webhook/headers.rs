@@ -0,0 +1,96 @@
+//! Header lookup used by the verification, whatever HTTP stack received the webhook.
+/*!
+[`HeaderSource`] is implemented for [`HeaderMap`], the one of the `http` crate used by `axum`,
+`hyper` and `reqwest`, for slices and vectors of `(name, value)` pairs and for
+[`CaseInsensitiveHeaders`], which any `(name, value)` iterator can be collected into.
+
+```
+# use bpay::c2b::webhook::headers::{CaseInsensitiveHeaders, HeaderSource};
+let headers = [("BinancePay-Nonce", "5K8264ILTKCH16CQ2502SI8ZNMTM67VS")];
+assert!(headers.header("binancepay-nonce").is_some());
+
+let headers: CaseInsensitiveHeaders = headers.into_iter().collect();
+assert_eq!(headers.header_str("binancepay-nonce").unwrap(), "5K8264ILTKCH16CQ2502SI8ZNMTM67VS");
+```
+*/
+
+use super::verification::VerificationError;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+
+/// Looks up the headers of a webhook request.
+pub trait HeaderSource {
+    /// Raw value of the header, `name` is in lowercase and compared case insensitively.
+    fn header(&self, name: &str) -> Option<&[u8]>;
+
+    /// Value of the header, which must be valid UTF-8.
+    fn header_str(&self, name: &str) -> Result<&str, VerificationError> {
+        let value = self
+            .header(name)
+            .ok_or_else(|| VerificationError::MissingHeader(name.into()))?;
+        std::str::from_utf8(value).map_err(|_| VerificationError::InvalidHeader(name.into()))
+    }
+}
+
+impl HeaderSource for HeaderMap {
+    fn header(&self, name: &str) -> Option<&[u8]> {
+        self.get(name).map(|value| value.as_bytes())
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<[u8]>> HeaderSource for [(K, V)] {
+    fn header(&self, name: &str) -> Option<&[u8]> {
+        self.iter()
+            .find(|(key, _)| key.as_ref().eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_ref())
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<[u8]>, const N: usize> HeaderSource for [(K, V); N] {
+    fn header(&self, name: &str) -> Option<&[u8]> {
+        self.as_slice().header(name)
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<[u8]>> HeaderSource for Vec<(K, V)> {
+    fn header(&self, name: &str) -> Option<&[u8]> {
+        self.as_slice().header(name)
+    }
+}
+
+impl<S: HeaderSource + ?Sized> HeaderSource for &S {
+    fn header(&self, name: &str) -> Option<&[u8]> {
+        (**self).header(name)
+    }
+}
+
+/// Headers keyed by their lowercase name, the last value of a repeated header wins.
+#[derive(Debug, Clone, Default)]
+pub struct CaseInsensitiveHeaders(HashMap<String, Vec<u8>>);
+
+impl CaseInsensitiveHeaders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: impl AsRef<str>, value: impl Into<Vec<u8>>) {
+        self.0
+            .insert(name.as_ref().to_ascii_lowercase(), value.into());
+    }
+}
+
+impl<K: AsRef<str>, V: Into<Vec<u8>>> FromIterator<(K, V)> for CaseInsensitiveHeaders {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut headers = Self::new();
+        for (name, value) in iter {
+            headers.insert(name, value);
+        }
+        headers
+    }
+}
+
+impl HeaderSource for CaseInsensitiveHeaders {
+    fn header(&self, name: &str) -> Option<&[u8]> {
+        self.0.get(&name.to_ascii_lowercase()).map(Vec::as_slice)
+    }
+}
@@ -1,6 +1,13 @@
 //! Contains all webhook related helpers.
 
+pub mod dedup;
+#[cfg(any(test, feature = "axum"))]
+pub mod handler;
+pub mod headers;
 pub mod notification;
+pub mod replay;
+pub mod rotation;
+pub mod router;
 pub mod verification;
 
 pub mod certificate {
@@ -8,12 +15,17 @@ pub mod certificate {
 
     use serde::{Deserialize, Serialize};
 
-    use super::verification::Verifier;
+    use super::verification::{VerificationError, Verifier};
+    use crate::c2b::validation::{Validate, Violations};
 
     /// Empty body of the query certificate request.
     #[derive(Serialize, Debug)]
     pub struct Certificate;
 
+    impl Validate for Certificate {
+        fn check(&self, _violations: &mut Violations) {}
+    }
+
     #[derive(Debug, Deserialize, Clone)]
     #[serde(rename_all = "camelCase")]
     pub struct CertificateResult {
@@ -24,8 +36,10 @@ pub mod certificate {
         pub cert_public: String,
     }
 
-    impl<'a> From<CertificateResult> for Verifier {
-        fn from(cert_result: CertificateResult) -> Self {
+    impl TryFrom<CertificateResult> for Verifier {
+        type Error = VerificationError;
+
+        fn try_from(cert_result: CertificateResult) -> Result<Self, Self::Error> {
             Self::new(cert_result.cert_public, cert_result.cert_serial)
         }
     }
@@ -3,6 +3,8 @@ Webhook notification payload parsing helpers.
 ```
 # use bpay::c2b::webhook::notification::{Notification, order::Currency};
 # use bpay::c2b::webhook::notification::order::BizStatus;
+# use bpay::c2b::amount::Amount;
+# use std::str::FromStr;
 # fn main() {
 let body = r#"{
     "bizType": "PAY",
@@ -21,7 +23,7 @@ match notification {
         assert_eq!(biz_id, 29383937493038367292);
         assert_eq!(biz_status, BizStatus::PaySuccess);
         # assert_eq!(order_detail.merchant_trade_no, "9825382937292");
-        # assert_eq!(order_detail.total_fee, 0.88);
+        # assert_eq!(order_detail.total_fee, Amount::from_str("0.88").unwrap());
         # assert_eq!(order_detail.currency, Currency::USDT);
     }
     _ => panic!("Unexpected notification type"),
@@ -34,16 +36,40 @@ pub mod batch_payout;
 pub mod order;
 pub mod refund;
 
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use serde_json::{self, Value};
 
 use crate::errors::Error;
-#[derive(Deserialize, Debug, Clone)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BizType {
     Pay,
     PayRefund,
     Payout,
+    /// A type this version doesn't know.
+    Other(String),
+}
+
+impl BizType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            BizType::Pay => "PAY",
+            BizType::PayRefund => "PAY_REFUND",
+            BizType::Payout => "PAYOUT",
+            BizType::Other(biz_type) => biz_type,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BizType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let biz_type = String::deserialize(deserializer)?;
+        Ok(match biz_type.as_str() {
+            "PAY" => BizType::Pay,
+            "PAY_REFUND" => BizType::PayRefund,
+            "PAYOUT" => BizType::Payout,
+            _ => BizType::Other(biz_type),
+        })
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -67,7 +93,8 @@ impl TryFrom<&str> for NotificationRequestParams {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Notification {
     Order {
         biz_id: u128,
@@ -84,6 +111,14 @@ pub enum Notification {
         biz_status: batch_payout::BizStatus,
         payout_detail: batch_payout::Payout,
     },
+    /// A notification type this version doesn't know, kept as received.
+    Unknown {
+        biz_type: String,
+        biz_id: u128,
+        biz_status: String,
+        /// The `data` JSON string.
+        raw_data: String,
+    },
 }
 
 impl TryFrom<NotificationRequestParams> for Notification {
@@ -106,6 +141,15 @@ impl TryFrom<NotificationRequestParams> for Notification {
                 biz_status: serde_json::from_value::<batch_payout::BizStatus>(params.biz_status)?,
                 payout_detail: serde_json::from_str::<batch_payout::Payout>(&params.data)?,
             }),
+            BizType::Other(biz_type) => Ok(Notification::Unknown {
+                biz_type,
+                biz_id: params.biz_id,
+                biz_status: match params.biz_status {
+                    Value::String(status) => status,
+                    status => status.to_string(),
+                },
+                raw_data: params.data,
+            }),
         }
     }
 }
@@ -120,7 +164,9 @@ impl TryFrom<&str> for Notification {
 
 #[cfg(test)]
 mod tests {
+    use crate::c2b::amount::Amount;
     use order::Currency;
+    use std::str::FromStr;
 
     use super::*;
 
@@ -142,8 +188,63 @@ mod tests {
                 assert_eq!(biz_id, 29383937493038367292);
                 assert_eq!(biz_status, order::BizStatus::PaySuccess);
                 assert_eq!(details.merchant_trade_no, "9825382937292");
-                assert_eq!(details.total_fee, 0.88);
+                assert_eq!(details.total_fee, Amount::from_str("0.88").unwrap());
                 assert_eq!(details.currency, Currency::USDT);
+                assert_eq!(details.transact_time, Some(1619508939664));
+                assert_eq!(details.transaction_id.unwrap(), "M_R_282737362839373");
+                assert!(details.payer_info.is_none());
+            }
+            _ => panic!("Unexpected notification type"),
+        }
+    }
+
+    #[test]
+    fn test_order_notification_with_payer_and_payment_info() {
+        let body = r#"{
+            "bizType": "PAY",
+            "data": "{\"merchantTradeNo\":\"9825382937292\",\"totalFee\":0.88000000,\"transactTime\":1619508939664,\"currency\":\"USDT\",\"openUserId\":\"1211HS10K81f4273ac031\",\"productType\":\"Food\",\"productName\":\"Ice Cream\",\"tradeType\":\"WEB\",\"transactionId\":\"M_R_282737362839373\",\"passThroughInfo\":\"cart-42\",\"commission\":\"0.0088\",\"payerInfo\":{\"firstName\":\"Ada\",\"lastName\":\"Lovelace\",\"walletId\":\"20b6fa2d\",\"country\":\"GB\"},\"paymentInfo\":{\"payerId\":354195960,\"payMethod\":\"funding\",\"paymentInstructions\":[{\"currency\":\"BUSD\",\"amount\":0.88,\"price\":1}],\"channel\":\"DEFAULT\"}}",
+            "bizId": 29383937493038367292,
+            "bizStatus": "PAY_SUCCESS"
+        }"#;
+        match Notification::try_from(body).unwrap() {
+            Notification::Order { order_detail, .. } => {
+                assert_eq!(order_detail.pass_through_info.unwrap(), "cart-42");
+                assert_eq!(
+                    order_detail.commission.unwrap(),
+                    Amount::from_str("0.0088").unwrap()
+                );
+                let payer = order_detail.payer_info.unwrap();
+                assert_eq!(payer.first_name.unwrap(), "Ada");
+                assert!(payer.middle_name.is_none());
+                let payment = order_detail.payment_info.unwrap();
+                assert_eq!(payment.payer_id, Some(354195960));
+                assert_eq!(payment.payment_instructions[0].currency, Currency::BUSD);
+                assert_eq!(
+                    payment.payment_instructions[0].amount,
+                    Amount::from_str("0.88").unwrap()
+                );
+            }
+            _ => panic!("Unexpected notification type"),
+        }
+    }
+
+    #[test]
+    fn test_closed_order_without_transact_time() {
+        let body = r#"{
+            "bizType": "PAY",
+            "data": "{\"merchantTradeNo\":\"9825382937292\",\"totalFee\":0.88000000,\"currency\":\"USDT\",\"productType\":\"Food\",\"productName\":\"Ice Cream\",\"tradeType\":\"WEB\"}",
+            "bizId": 29383937493038367292,
+            "bizStatus": "PAY_CLOSED"
+        }"#;
+        match Notification::try_from(body).unwrap() {
+            Notification::Order {
+                biz_status,
+                order_detail,
+                ..
+            } => {
+                assert_eq!(biz_status, order::BizStatus::PayClosed);
+                assert!(order_detail.transact_time.is_none());
+                assert!(order_detail.transaction_id.is_none());
             }
             _ => panic!("Unexpected notification type"),
         }
@@ -169,7 +270,12 @@ mod tests {
                 assert_eq!(biz_id, 123289163323899904);
                 assert_eq!(biz_status, refund::BizStatus::RefundSuccess);
                 assert_eq!(details.merchant_trade_no, "6177e6ae81ce6f001b4a6233");
-                assert_eq!(details.refund_info.order_amount, "0.01000000");
+                assert_eq!(
+                    details.refund_info.order_amount,
+                    Amount::from_str("0.01").unwrap()
+                );
+                assert_eq!(details.transact_time, Some(1635248421335));
+                assert_eq!(details.commission.unwrap(), Amount::zero());
             }
             _ => panic!("Unexpected notification type"),
         }
@@ -194,7 +300,34 @@ mod tests {
                 assert_eq!(biz_id, 29383937493038367292);
                 assert_eq!(biz_status, batch_payout::BizStatus::Success);
                 assert_eq!(details.request_id, "gg8127129");
-                assert_eq!(details.total_amount, 2.0);
+                assert_eq!(details.total_amount, Amount::from(2u32));
+            }
+            _ => panic!("Unexpected notification type"),
+        }
+    }
+
+    #[test]
+    fn test_unknown_notifications_are_kept() {
+        let body = r#"{"bizType":"PAY_DEDUCT","data":"{\"amount\":1}","bizId":1,"bizStatus":"DEDUCT_SUCCESS"}"#;
+        match Notification::try_from(body).unwrap() {
+            Notification::Unknown {
+                biz_type,
+                biz_id,
+                biz_status,
+                raw_data,
+            } => {
+                assert_eq!(biz_type, "PAY_DEDUCT");
+                assert_eq!(biz_id, 1);
+                assert_eq!(biz_status, "DEDUCT_SUCCESS");
+                assert_eq!(raw_data, r#"{"amount":1}"#);
+            }
+            _ => panic!("Unexpected notification type"),
+        }
+
+        let body = r#"{"bizType":"PAY_REFUND","data":"{\"merchantTradeNo\":\"6177e6ae81ce6f001b4a6233\",\"totalFee\":0.01,\"transactTime\":1635248421335,\"refundInfo\":{\"orderAmount\":\"0.01000000\",\"duplicateRequest\":\"N\",\"payerOpenId\":\"9aa0a8bb21cf5fbf049aad7db35dc3d3\",\"prepayId\":\"123289163323899904\",\"refundRequestId\":\"68711039982968853\",\"refundedAmount\":\"0.01000000\",\"remainingAttempts\":9,\"refundAmount\":\"0.01000000\"},\"currency\":\"BUSD\",\"commission\":0,\"openUserId\":\"b5ec36baaa5ab9a5cfb1c29c2057bd81\",\"productType\":\"LIVE_STREAM\",\"productName\":\"LIVE_STREAM\",\"tradeType\":\"APP\"}","bizId":123289163323899904,"bizStatus":"REFUND_PENDING"}"#;
+        match Notification::try_from(body).unwrap() {
+            Notification::Refund { biz_status, .. } => {
+                assert_eq!(biz_status, refund::BizStatus::Unknown)
             }
             _ => panic!("Unexpected notification type"),
         }
@@ -1,13 +1,15 @@
 //! Order notification json deserialization format.
 
+use crate::c2b::amount::Amount;
+use crate::c2b::currency::Currency;
 pub use crate::c2b::payout::query::BatchStatus as BizStatus;
 use serde::Deserialize;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "testing"))]
 use serde::Serialize;
 
-#[derive(Deserialize, Debug)]
-#[cfg_attr(test, derive(Serialize))]
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Status {
     /// Accepted the request, will process it soon.  
@@ -27,10 +29,14 @@ pub enum Status {
 
     /// There remains transfers with unknown results after retry limit, will not retry further
     Canceled,
+
+    /// A status this version doesn't know.
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Deserialize, Debug)]
-#[cfg_attr(test, derive(Serialize))]
+#[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct Payout {
     /// The passed-in request ID
@@ -41,10 +47,10 @@ pub struct Payout {
     pub merchant_id: u64,
 
     /// The request currency.
-    pub currency: String,
+    pub currency: Currency,
 
     /// Total transfer amount in this batch.
-    pub total_amount: f64,
+    pub total_amount: Amount,
 
     /// Total number of transfers in the batch.
     pub total_number: u16,
@@ -53,6 +59,7 @@ pub struct Payout {
 #[cfg(test)]
 mod tests {
     use crate::c2b::tests::test_request_serialize_deserialize;
+    use std::str::FromStr;
 
     test_request_serialize_deserialize!(
         (
@@ -62,8 +69,8 @@ mod tests {
                 request_id: "gg8127129".to_string(),
                 batch_status: Status::Success,
                 merchant_id: 100100006288,
-                currency: "BUSD".to_string(),
-                total_amount: 2.0,
+                currency: Currency::BUSD,
+                total_amount: Amount::from_str("2.00000000").unwrap(),
                 total_number: 2,
             }
         )
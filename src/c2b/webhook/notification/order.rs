@@ -1,16 +1,25 @@
 //! Order notification json deserialization format.
 
+use crate::c2b::amount::Amount;
 pub use crate::c2b::order::create::{Currency, TerminalType};
 use serde::Deserialize;
+#[cfg(any(test, feature = "testing"))]
+use serde::Serialize;
 
-#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum BizStatus {
     PaySuccess,
     PayClosed,
+
+    /// A status this version doesn't know.
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct OrderNotification {
     ///	letter or digit, no other symbol allowed	The order id, Unique identifier for the request
@@ -26,11 +35,82 @@ pub struct OrderNotification {
     pub trade_type: TerminalType,
 
     ///	order amount
-    pub total_fee: f64,
+    pub total_fee: Amount,
 
     ///	String order currency
     pub currency: Currency,
 
     ///	Consumer unique id
     pub open_user_id: Option<String>,
+
+    /// Order transaction time, in milliseconds, missing e.g. from `PAY_CLOSED` notifications
+    pub transact_time: Option<u64>,
+
+    /// Issued once the payment is successful, matches the ledger entries
+    pub transaction_id: Option<String>,
+
+    /// Pass through info, from the create order request
+    pub pass_through_info: Option<String>,
+
+    /// Commission deducted from the order amount
+    pub commission: Option<Amount>,
+
+    /// Only merchants approved by Binance Operation's approval receive this payer information
+    pub payer_info: Option<PayerInfo>,
+
+    /// How the order was paid
+    pub payment_info: Option<PaymentInfo>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
+#[serde(rename_all = "camelCase")]
+pub struct PayerInfo {
+    pub first_name: Option<String>,
+    pub middle_name: Option<String>,
+    pub last_name: Option<String>,
+
+    /// Binance wallet id of the payer
+    pub wallet_id: Option<String>,
+    pub country: Option<String>,
+    pub city: Option<String>,
+    pub address: Option<String>,
+
+    /// Type of the identity document
+    pub identity_type: Option<String>,
+    pub identity_number: Option<String>,
+    pub date_of_birth: Option<String>,
+    pub place_of_birth: Option<String>,
+    pub nationality: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentInfo {
+    /// Binance id of the payer
+    pub payer_id: Option<u64>,
+
+    /// Payment method, e.g. "funding" or "card"
+    pub pay_method: Option<String>,
+
+    /// Currencies the order was paid with
+    #[serde(default)]
+    pub payment_instructions: Vec<PaymentInstruction>,
+
+    /// Payment channel, e.g. "DEFAULT"
+    pub channel: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentInstruction {
+    pub currency: Currency,
+
+    /// Amount paid in the currency
+    pub amount: Amount,
+
+    /// Price of the currency in the order currency
+    pub price: Amount,
 }
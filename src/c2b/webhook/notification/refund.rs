@@ -1,14 +1,25 @@
+pub use super::order::PayerInfo;
+use crate::c2b::amount::Amount;
+use crate::c2b::currency::Currency;
 pub use crate::c2b::refund::initiate::Response as RefundInfo;
 use serde::Deserialize;
+#[cfg(any(test, feature = "testing"))]
+use serde::Serialize;
 
-#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum BizStatus {
     RefundSuccess,
     RefundRejected,
+
+    /// A status this version doesn't know.
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(any(test, feature = "testing"), derive(Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct Refund {
     /// The order id, Unique identifier for the request
@@ -23,14 +34,26 @@ pub struct Refund {
     pub trade_type: String,
 
     /// order amount
-    pub total_fee: f64,
+    pub total_fee: Amount,
 
     /// order currency
-    pub currency: String,
+    pub currency: Currency,
 
     /// Consumer unique id
     pub open_user_id: String,
 
+    /// Payment time of the refunded order, in milliseconds, when Binance sends it
+    pub transact_time: Option<u64>,
+
+    /// Commission Binance charged on the refunded order
+    pub commission: Option<Amount>,
+
+    /// Pass through info given when the refunded order was created
+    pub pass_through_info: Option<String>,
+
+    /// Only merchant got approved by Binance Operation's approval will receive this payerInfo
+    pub payer_info: Option<PayerInfo>,
+
     /// Only merchant got approved by Binance Operation's approval will receive this payerInfo	payer information, refer to
     pub refund_info: RefundInfo,
 }
@@ -0,0 +1,297 @@
+//! Rejects webhooks which are too old or were already delivered.
+/*!
+The signature proves a webhook was sent by Binance, not that it is sent for the first time.
+[`ReplayGuard`] additionally requires the `BinancePay-Timestamp` header to be within a window
+around the current time and the `BinancePay-Nonce` header to be unseen. The nonces are kept in a
+[`NonceStore`] for twice the window, older deliveries are rejected by their timestamp anyway.
+
+```rust,no_run
+# use bpay::c2b::webhook::replay::{FileNonceStore, ReplayGuard};
+# use bpay::c2b::webhook::verification::Verifier;
+# use reqwest::header::HeaderMap;
+# use std::time::Duration;
+# #[tokio::main]
+# async fn main() -> bpay::errors::Result<()> {
+# let headers = HeaderMap::new();
+# let body = "";
+# let verifier = Verifier::new("certificate".into(), "serial".into())?;
+let guard = ReplayGuard::new(FileNonceStore::open("nonces.log")?)
+    .window(Duration::from_secs(60));
+guard.verify(&verifier, &headers, body).await?;
+# Ok(())
+# }
+```
+*/
+
+use super::headers::HeaderSource;
+use super::verification::{VerificationError, Verify};
+use crate::errors::Result;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+
+/// Remembers the nonces of the accepted webhooks.
+#[async_trait::async_trait]
+pub trait NonceStore: Send + Sync {
+    /// Records the nonce for `ttl`, returns `false` if it is already recorded.
+    async fn insert(&self, nonce: &str, ttl: Duration) -> Result<bool>;
+}
+
+/// Nonces kept in memory, they are lost on restart.
+#[derive(Debug, Default)]
+pub struct MemoryNonceStore {
+    /// Expiry of each nonce.
+    nonces: Mutex<HashMap<String, SystemTime>>,
+}
+
+impl MemoryNonceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl NonceStore for MemoryNonceStore {
+    async fn insert(&self, nonce: &str, ttl: Duration) -> Result<bool> {
+        let now = SystemTime::now();
+        let mut nonces = self.nonces.lock().unwrap();
+        nonces.retain(|_, expiry| *expiry > now);
+        Ok(nonces.insert(nonce.into(), now + ttl).is_none())
+    }
+}
+
+/// Nonces appended to a file, so they survive a restart.
+/// Each line holds the expiry in seconds since the epoch and the nonce.
+#[derive(Debug)]
+pub struct FileNonceStore {
+    path: PathBuf,
+    state: tokio::sync::Mutex<FileState>,
+}
+
+#[derive(Debug)]
+struct FileState {
+    /// Expiry of each unexpired nonce.
+    nonces: HashMap<String, u64>,
+    /// Lines in the file, the expired nonces included.
+    lines: usize,
+}
+
+impl FileNonceStore {
+    /// Loads the unexpired nonces of the file and rewrites it without the expired ones.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let now = unix_secs(SystemTime::now());
+        let mut nonces = HashMap::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                let line = line?;
+                if let Some((expiry, nonce)) = line.split_once(' ') {
+                    match expiry.parse::<u64>() {
+                        Ok(expiry) if expiry > now => nonces.insert(nonce.to_string(), expiry),
+                        _ => None,
+                    };
+                }
+            }
+        }
+        std::fs::write(&path, file_contents(&nonces))?;
+        Ok(Self {
+            path,
+            state: tokio::sync::Mutex::new(FileState {
+                lines: nonces.len(),
+                nonces,
+            }),
+        })
+    }
+
+    /// Rewrites the file with the unexpired nonces only, through a temporary file so a crash
+    /// never leaves it truncated.
+    async fn compact(&self, nonces: &HashMap<String, u64>) -> Result<()> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        tokio::fs::write(&tmp, file_contents(nonces)).await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl NonceStore for FileNonceStore {
+    /// Drops the expired nonces and compacts the file once they make up most of it.
+    async fn insert(&self, nonce: &str, ttl: Duration) -> Result<bool> {
+        let now = SystemTime::now();
+        let mut state = self.state.lock().await;
+        state.nonces.retain(|_, expiry| *expiry > unix_secs(now));
+        if state.nonces.contains_key(nonce) {
+            return Ok(false);
+        }
+        let expiry = unix_secs(now + ttl);
+        state.nonces.insert(nonce.into(), expiry);
+        if state.lines >= 2 * state.nonces.len() {
+            self.compact(&state.nonces).await?;
+            state.lines = state.nonces.len();
+        } else {
+            let mut file = tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(&self.path)
+                .await?;
+            file.write_all(format!("{expiry} {nonce}\n").as_bytes())
+                .await?;
+            state.lines += 1;
+        }
+        Ok(true)
+    }
+}
+
+fn file_contents(nonces: &HashMap<String, u64>) -> String {
+    nonces
+        .iter()
+        .map(|(nonce, expiry)| format!("{expiry} {nonce}\n"))
+        .collect()
+}
+
+/// Checks the freshness and the uniqueness of the webhooks.
+pub struct ReplayGuard<S> {
+    store: S,
+    window: Duration,
+}
+
+impl<S: NonceStore> ReplayGuard<S> {
+    /// Guard accepting timestamps up to 5 minutes away from the current time.
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            window: Duration::from_secs(5 * 60),
+        }
+    }
+
+    /// Maximum difference between the webhook timestamp and the current time.
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Verifies the signature, then the freshness and the uniqueness of the webhook.
+    /// Only signed webhooks reach the nonce store.
+    pub async fn verify<'a, H: HeaderSource + Sync + ?Sized>(
+        &self,
+        verifier: &impl Verify<'a>,
+        headers: &'a H,
+        body: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        verifier.verify(headers, body)?;
+        self.check(headers).await
+    }
+
+    /// Checks the freshness and the uniqueness of a webhook whose signature is already verified.
+    pub async fn check<H: HeaderSource + Sync + ?Sized>(&self, headers: &H) -> Result<()> {
+        let timestamp = headers.header_str("binancepay-timestamp")?;
+        let sent_at = timestamp
+            .parse::<u64>()
+            .map(|millis| UNIX_EPOCH + Duration::from_millis(millis))
+            .map_err(|_| VerificationError::InvalidHeader("binancepay-timestamp".into()))?;
+        let now = SystemTime::now();
+        let age = now.duration_since(sent_at).unwrap_or_else(|e| e.duration());
+        if age > self.window {
+            return Err(VerificationError::Stale(timestamp.into()).into());
+        }
+        let nonce = headers.header_str("binancepay-nonce")?;
+        if !self.store.insert(nonce, self.window * 2).await? {
+            return Err(VerificationError::Replayed(nonce.into()).into());
+        }
+        Ok(())
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::Error;
+    use crate::testing::KeyPair;
+    use reqwest::header::HeaderMap;
+
+    fn signed_headers(timestamp: &str, nonce: &str, body: &str) -> HeaderMap {
+        KeyPair::bundled()
+            .unwrap()
+            .signer()
+            .unwrap()
+            .sign_with(timestamp, nonce, body)
+            .unwrap()
+            .header_map()
+            .unwrap()
+    }
+
+    fn now_millis() -> String {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_rejects_stale_and_replayed_webhooks() {
+        let verifier = KeyPair::bundled()
+            .unwrap()
+            .signer()
+            .unwrap()
+            .verifier()
+            .unwrap();
+        let guard = ReplayGuard::new(MemoryNonceStore::new()).window(Duration::from_secs(60));
+        let body = r#"{"bizType":"PAY"}"#;
+
+        let headers = signed_headers(&now_millis(), "nonce1", body);
+        guard.verify(&verifier, &headers, body).await.unwrap();
+        assert!(matches!(
+            guard.verify(&verifier, &headers, body).await,
+            Err(Error::Verification(VerificationError::Replayed(_)))
+        ));
+
+        let headers = signed_headers("1646584911979", "nonce2", body);
+        assert!(matches!(
+            guard.verify(&verifier, &headers, body).await,
+            Err(Error::Verification(VerificationError::Stale(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_file_store_survives_reopening() {
+        let path =
+            std::env::temp_dir().join(format!("bpay-nonces-{}", crate::utils::create_nonce(8)));
+        let ttl = Duration::from_secs(60);
+        {
+            let store = FileNonceStore::open(&path).unwrap();
+            assert!(store.insert("nonce1", ttl).await.unwrap());
+            assert!(store.insert("expired", Duration::ZERO).await.unwrap());
+        }
+        let store = FileNonceStore::open(&path).unwrap();
+        assert!(!store.insert("nonce1", ttl).await.unwrap());
+        assert!(store.insert("expired", ttl).await.unwrap());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_store_prunes_expired_nonces() {
+        let path =
+            std::env::temp_dir().join(format!("bpay-nonces-{}", crate::utils::create_nonce(8)));
+        let store = FileNonceStore::open(&path).unwrap();
+        for i in 0..10 {
+            assert!(store
+                .insert(&format!("expired{i}"), Duration::ZERO)
+                .await
+                .unwrap());
+        }
+        assert!(store.state.lock().await.nonces.len() <= 1);
+        assert!(std::fs::read_to_string(&path).unwrap().lines().count() <= 2);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,244 @@
+//! Verification against every certificate Binance currently signs the webhooks with.
+/*!
+Binance rotates its signing certificates, a webhook names the one it was signed with in the
+`BinancePay-Certificate-SN` header. [`RotatingVerifier`] keeps all the certificates keyed by
+their serial and fetches them again when a webhook names an unknown one. The refreshes are
+rate limited and shared between the tasks verifying concurrently.
+
+```rust,no_run
+# use bpay::c2b::webhook::rotation::RotatingVerifier;
+# use bpay::client::Client;
+# use reqwest::header::HeaderMap;
+# #[tokio::main]
+# async fn main() -> bpay::errors::Result<()> {
+# let headers = HeaderMap::new();
+# let body = "";
+let verifier = RotatingVerifier::from_api(Client::from_env()).await?;
+verifier.verify(&headers, body).await?;
+# Ok(())
+# }
+```
+*/
+
+use super::headers::HeaderSource;
+use super::verification::{VerificationError, Verifier, Verify};
+use crate::api::webhook::certificate::Certificate;
+use crate::client::Client;
+use crate::errors::{Error, Result};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::Instant;
+
+/// Verifier holding every certificate returned by the API, keyed by serial.
+pub struct RotatingVerifier {
+    client: Client,
+    verifiers: RwLock<HashMap<String, Verifier>>,
+    last_refresh: Mutex<Option<Instant>>,
+    min_refresh_interval: Duration,
+}
+
+impl RotatingVerifier {
+    /// Fetches the current certificates.
+    pub async fn from_api(client: Client) -> Result<Self> {
+        let verifier = Self::new(client);
+        verifier.refresh().await?;
+        Ok(verifier)
+    }
+
+    /// Verifier without any certificate, they are fetched by the first verification.
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            verifiers: RwLock::new(HashMap::new()),
+            last_refresh: Mutex::new(None),
+            min_refresh_interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Minimum time between two fetches of the certificates, 60s by default.
+    pub fn min_refresh_interval(mut self, interval: Duration) -> Self {
+        self.min_refresh_interval = interval;
+        self
+    }
+
+    /// Serials of the certificates currently known.
+    pub async fn serials(&self) -> Vec<String> {
+        self.verifiers.read().await.keys().cloned().collect()
+    }
+
+    /// Fetches the certificates and replaces the known ones, regardless of the rate limit.
+    /// The known ones are kept if the API returns no valid certificate.
+    pub async fn refresh(&self) -> Result<()> {
+        let mut last_refresh = self.last_refresh.lock().await;
+        self.fetch().await?;
+        *last_refresh = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Verifies the webhook with the certificate named by its `BinancePay-Certificate-SN` header.
+    pub async fn verify<H: HeaderSource + Sync + ?Sized>(
+        &self,
+        headers: &H,
+        body: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let serial = headers.header_str("binancepay-certificate-sn")?;
+        if let Some(verifier) = self.verifier(serial).await {
+            return Ok(verifier.verify(headers, body)?);
+        }
+        self.refresh_for(serial).await?;
+        match self.verifier(serial).await {
+            Some(verifier) => Ok(verifier.verify(headers, body)?),
+            None => Err(VerificationError::UnknownSerial(serial.into()).into()),
+        }
+    }
+
+    async fn verifier(&self, serial: &str) -> Option<Verifier> {
+        self.verifiers.read().await.get(serial).cloned()
+    }
+
+    /// Refreshes unless another task just did, or the last refresh is too recent.
+    async fn refresh_for(&self, serial: &str) -> Result<()> {
+        let mut last_refresh = self.last_refresh.lock().await;
+        if self.verifiers.read().await.contains_key(serial) {
+            return Ok(());
+        }
+        if last_refresh.is_some_and(|at| at.elapsed() < self.min_refresh_interval) {
+            return Ok(());
+        }
+        let fetched = self.fetch().await;
+        // Failures count too, so an unreachable API isn't hammered.
+        *last_refresh = Some(Instant::now());
+        fetched
+    }
+
+    /// Certificates which can't be parsed are skipped, the webhooks they signed are rejected.
+    async fn fetch(&self) -> Result<()> {
+        let verifiers: HashMap<_, _> = Certificate
+            .fetch_all(&self.client)
+            .await?
+            .into_iter()
+            .filter_map(|certificate| {
+                let serial = certificate.cert_serial.clone();
+                match Verifier::try_from(certificate) {
+                    Ok(verifier) => Some((serial, verifier)),
+                    Err(e) => {
+                        log::warn!("Skipping the certificate {serial}: {e}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        if verifiers.is_empty() {
+            return Err(Error::Msg(
+                "No valid certificate returned, keeping the known ones".into(),
+            ));
+        }
+        *self.verifiers.write().await = verifiers;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::KeyPair;
+    use crate::transport::{HttpResponse, InMemoryTransport};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_refreshes_on_unknown_serial() {
+        let key_pair = KeyPair::bundled().unwrap();
+        let signer = key_pair.signer().unwrap();
+        let rotated = Arc::new(AtomicBool::new(false));
+        let certificates = {
+            let rotated = rotated.clone();
+            let certificate = key_pair.certificate();
+            let certificate = json!({
+                "certSerial": certificate.cert_serial,
+                "certPublic": certificate.cert_public,
+            });
+            move |_: &_| {
+                let data = if rotated.load(Ordering::SeqCst) {
+                    json!([certificate])
+                } else {
+                    json!([{"certSerial": "old", "certPublic": certificate["certPublic"]}])
+                };
+                HttpResponse::ok(
+                    json!({"status": "SUCCESS", "code": "000000", "data": data}).to_string(),
+                )
+            }
+        };
+        let transport = Arc::new(InMemoryTransport::new(certificates));
+        let client = Client::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let verifier = RotatingVerifier::from_api(client)
+            .await
+            .unwrap()
+            .min_refresh_interval(Duration::from_secs(3600));
+        assert_eq!(verifier.serials().await, ["old"]);
+
+        let body = r#"{"bizType":"PAY"}"#;
+        let headers = signer
+            .sign_with("1646584911979", "nonce", body)
+            .unwrap()
+            .header_map()
+            .unwrap();
+
+        // The certificates were just fetched, the unknown serial is rejected without a refresh.
+        assert!(matches!(
+            verifier.verify(&headers, body).await,
+            Err(Error::Verification(VerificationError::UnknownSerial(_)))
+        ));
+        assert_eq!(transport.requests().len(), 1);
+
+        rotated.store(true, Ordering::SeqCst);
+        let verifier = Arc::new(verifier.min_refresh_interval(Duration::ZERO));
+        let verifications: Vec<_> = (0..8)
+            .map(|_| {
+                let (verifier, headers) = (verifier.clone(), headers.clone());
+                tokio::spawn(async move { verifier.verify(&headers, body).await })
+            })
+            .collect();
+        for verification in verifications {
+            verification.await.unwrap().unwrap();
+        }
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_keeps_certificates_when_none_is_valid() {
+        let certificate = KeyPair::bundled().unwrap().certificate();
+        let certificate = json!({
+            "certSerial": certificate.cert_serial,
+            "certPublic": certificate.cert_public,
+        });
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let transport = {
+            let fetches = fetches.clone();
+            InMemoryTransport::new(move |_| {
+                let data = match fetches.fetch_add(1, Ordering::SeqCst) {
+                    0 => json!([certificate]),
+                    1 => json!([]),
+                    _ => json!([{"certSerial": "new", "certPublic": "not a certificate"}]),
+                };
+                HttpResponse::ok(
+                    json!({"status": "SUCCESS", "code": "000000", "data": data}).to_string(),
+                )
+            })
+        };
+        let client = Client::builder().transport(transport).build().unwrap();
+        let verifier = RotatingVerifier::from_api(client).await.unwrap();
+        let serials = verifier.serials().await;
+
+        // An empty list, then one without any valid certificate.
+        assert!(verifier.refresh().await.is_err());
+        assert!(verifier.refresh().await.is_err());
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+        assert_eq!(verifier.serials().await, serials);
+    }
+}
@@ -0,0 +1,341 @@
+//! Dispatches the notifications to the async handlers registered for their type and status.
+/*!
+Handlers are registered per notification type, e.g. [`NotificationRouter::on_order`], or for a
+single status, e.g. [`NotificationRouter::on_refund_status`]. Every matching handler runs, the
+status ones first, and their errors are collected in the [`Dispatched`] outcome which maps to
+the [`Acknowledgement`] Binance expects.
+
+```
+# use bpay::c2b::webhook::notification::{order, Notification};
+# use bpay::c2b::webhook::router::NotificationRouter;
+# #[tokio::main]
+# async fn main() {
+let router = NotificationRouter::new()
+    .on_order_status(order::BizStatus::PaySuccess, |event| async move {
+        println!("{} paid", event.detail.merchant_trade_no);
+        Ok(())
+    })
+    .on_refund(|event| async move { Err(format!("refund {} not handled", event.biz_id).into()) });
+
+# let body = r#"{"bizType":"PAY","data":"{\"merchantTradeNo\":\"9825382937292\",\"totalFee\":0.88000000,\"transactTime\":1619508939664,\"currency\":\"USDT\",\"openUserId\":\"1211HS10K81f4273ac031\",\"productType\":\"Food\",\"productName\":\"Ice Cream\",\"tradeType\":\"WEB\",\"transactionId\":\"M_R_282737362839373\"}","bizId":29383937493038367292,"bizStatus":"PAY_SUCCESS"}"#;
+let dispatched = router.dispatch(Notification::try_from(body).unwrap()).await;
+assert_eq!(dispatched.handled, 1);
+assert!(dispatched.acknowledgement().is_success());
+# }
+```
+*/
+
+use super::notification::{batch_payout, order, refund, Notification};
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Error returned by a handler.
+pub type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+
+type HandlerFuture = Pin<Box<dyn Future<Output = Result<(), HandlerError>> + Send>>;
+type Handler<S, D> = Box<dyn Fn(Event<S, D>) -> HandlerFuture + Send + Sync>;
+
+/// Notification passed to the handlers, with the details of its type.
+#[derive(Debug, Clone)]
+pub struct Event<S, D> {
+    pub biz_id: u128,
+    pub biz_status: S,
+    pub detail: D,
+}
+
+pub type OrderEvent = Event<order::BizStatus, order::OrderNotification>;
+pub type RefundEvent = Event<refund::BizStatus, refund::Refund>;
+pub type PayoutEvent = Event<batch_payout::BizStatus, batch_payout::Payout>;
+
+struct Handlers<S, D> {
+    any: Vec<Handler<S, D>>,
+    by_status: Vec<(S, Handler<S, D>)>,
+}
+
+impl<S, D> Default for Handlers<S, D> {
+    fn default() -> Self {
+        Self {
+            any: vec![],
+            by_status: vec![],
+        }
+    }
+}
+
+impl<S: PartialEq + Clone, D: Clone> Handlers<S, D> {
+    fn boxed<F, Fut>(handler: F) -> Handler<S, D>
+    where
+        F: Fn(Event<S, D>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), HandlerError>> + Send + 'static,
+    {
+        Box::new(move |event| Box::pin(handler(event)))
+    }
+
+    /// Runs the matching handlers, returns how many ran.
+    async fn run(&self, event: Event<S, D>, errors: &mut Vec<HandlerError>) -> usize {
+        let handlers = self
+            .by_status
+            .iter()
+            .filter(|(status, _)| *status == event.biz_status)
+            .map(|(_, handler)| handler)
+            .chain(&self.any);
+        let mut handled = 0;
+        for handler in handlers {
+            handled += 1;
+            if let Err(e) = handler(event.clone()).await {
+                errors.push(e);
+            }
+        }
+        handled
+    }
+}
+
+/// Async handlers keyed by notification type and status.
+#[derive(Default)]
+pub struct NotificationRouter {
+    orders: Handlers<order::BizStatus, order::OrderNotification>,
+    refunds: Handlers<refund::BizStatus, refund::Refund>,
+    payouts: Handlers<batch_payout::BizStatus, batch_payout::Payout>,
+}
+
+impl NotificationRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handles every order notification.
+    pub fn on_order<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(OrderEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), HandlerError>> + Send + 'static,
+    {
+        self.orders.any.push(Handlers::boxed(handler));
+        self
+    }
+
+    /// Handles the order notifications with the given status.
+    pub fn on_order_status<F, Fut>(mut self, status: order::BizStatus, handler: F) -> Self
+    where
+        F: Fn(OrderEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), HandlerError>> + Send + 'static,
+    {
+        self.orders
+            .by_status
+            .push((status, Handlers::boxed(handler)));
+        self
+    }
+
+    /// Handles every refund notification.
+    pub fn on_refund<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(RefundEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), HandlerError>> + Send + 'static,
+    {
+        self.refunds.any.push(Handlers::boxed(handler));
+        self
+    }
+
+    /// Handles the refund notifications with the given status.
+    pub fn on_refund_status<F, Fut>(mut self, status: refund::BizStatus, handler: F) -> Self
+    where
+        F: Fn(RefundEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), HandlerError>> + Send + 'static,
+    {
+        self.refunds
+            .by_status
+            .push((status, Handlers::boxed(handler)));
+        self
+    }
+
+    /// Handles every payout notification.
+    pub fn on_payout<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(PayoutEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), HandlerError>> + Send + 'static,
+    {
+        self.payouts.any.push(Handlers::boxed(handler));
+        self
+    }
+
+    /// Handles the payout notifications with the given status.
+    pub fn on_payout_status<F, Fut>(mut self, status: batch_payout::BizStatus, handler: F) -> Self
+    where
+        F: Fn(PayoutEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), HandlerError>> + Send + 'static,
+    {
+        self.payouts
+            .by_status
+            .push((status, Handlers::boxed(handler)));
+        self
+    }
+
+    /// Runs every handler matching the notification, even when one of them fails.
+    pub async fn dispatch(&self, notification: Notification) -> Dispatched {
+        let mut errors = vec![];
+        let handled = match notification {
+            Notification::Order {
+                biz_id,
+                biz_status,
+                order_detail,
+            } => {
+                let event = Event {
+                    biz_id,
+                    biz_status,
+                    detail: order_detail,
+                };
+                self.orders.run(event, &mut errors).await
+            }
+            Notification::Refund {
+                biz_id,
+                biz_status,
+                refund_detail,
+            } => {
+                let event = Event {
+                    biz_id,
+                    biz_status,
+                    detail: refund_detail,
+                };
+                self.refunds.run(event, &mut errors).await
+            }
+            Notification::Payout {
+                biz_id,
+                biz_status,
+                payout_detail,
+            } => {
+                let event = Event {
+                    biz_id,
+                    biz_status,
+                    detail: payout_detail,
+                };
+                self.payouts.run(event, &mut errors).await
+            }
+            Notification::Unknown {
+                biz_type, biz_id, ..
+            } => {
+                log::warn!("No handler for the {biz_type} notification {biz_id}");
+                0
+            }
+        };
+        for e in &errors {
+            log::error!("Notification handler failed: {e}");
+        }
+        Dispatched { handled, errors }
+    }
+}
+
+/// Outcome of [`NotificationRouter::dispatch`].
+#[derive(Debug)]
+pub struct Dispatched {
+    /// Number of handlers which ran, `0` if none is registered for the notification or its
+    /// type is unknown.
+    pub handled: usize,
+    pub errors: Vec<HandlerError>,
+}
+
+impl Dispatched {
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// `SUCCESS` if every handler succeeded, otherwise `FAIL` with the errors so Binance
+    /// sends the notification again.
+    pub fn acknowledgement(&self) -> Acknowledgement {
+        if self.is_success() {
+            Acknowledgement::success()
+        } else {
+            let message = self
+                .errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Acknowledgement::fail(message)
+        }
+    }
+}
+
+/// Body of the reply to a notification request.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Acknowledgement {
+    /// `SUCCESS` or `FAIL`.
+    pub return_code: String,
+    pub return_message: Option<String>,
+}
+
+impl Acknowledgement {
+    pub fn success() -> Self {
+        Self {
+            return_code: "SUCCESS".into(),
+            return_message: None,
+        }
+    }
+
+    pub fn fail(message: impl Into<String>) -> Self {
+        Self {
+            return_code: "FAIL".into(),
+            return_message: Some(message.into()),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.return_code == "SUCCESS"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const REFUND: &str = r#"{"bizType":"PAY_REFUND","data":"{\"merchantTradeNo\":\"6177e6ae81ce6f001b4a6233\",\"totalFee\":0.01,\"transactTime\":1635248421335,\"refundInfo\":{\"orderAmount\":\"0.01000000\",\"duplicateRequest\":\"N\",\"payerOpenId\":\"9aa0a8bb21cf5fbf049aad7db35dc3d3\",\"prepayId\":\"123289163323899904\",\"refundRequestId\":\"68711039982968853\",\"refundedAmount\":\"0.01000000\",\"remainingAttempts\":9,\"refundAmount\":\"0.01000000\"},\"currency\":\"BUSD\",\"commission\":0,\"openUserId\":\"b5ec36baaa5ab9a5cfb1c29c2057bd81\",\"productType\":\"LIVE_STREAM\",\"productName\":\"LIVE_STREAM\",\"tradeType\":\"APP\"}","bizId":123289163323899904,"bizStatus":"REFUND_SUCCESS"}"#;
+
+    #[tokio::test]
+    async fn test_dispatch_to_matching_handlers() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let record = |name: &'static str| {
+            let calls = calls.clone();
+            move |_event: RefundEvent| {
+                calls.lock().unwrap().push(name);
+                async { Ok(()) }
+            }
+        };
+        let router = NotificationRouter::new()
+            .on_refund(record("any"))
+            .on_refund_status(refund::BizStatus::RefundRejected, record("rejected"))
+            .on_refund_status(refund::BizStatus::RefundSuccess, record("success"))
+            .on_order(|_| async { panic!("not an order") });
+
+        let dispatched = router
+            .dispatch(Notification::try_from(REFUND).unwrap())
+            .await;
+        assert_eq!(dispatched.handled, 2);
+        assert_eq!(dispatched.acknowledgement(), Acknowledgement::success());
+        assert_eq!(*calls.lock().unwrap(), ["success", "any"]);
+    }
+
+    #[tokio::test]
+    async fn test_collects_handler_errors() {
+        let router = NotificationRouter::new()
+            .on_refund(|event| async move { Err(format!("refund {} failed", event.biz_id).into()) })
+            .on_refund(|_| async { Err("storage unavailable".into()) });
+
+        let dispatched = router
+            .dispatch(Notification::try_from(REFUND).unwrap())
+            .await;
+        assert_eq!(dispatched.handled, 2);
+        assert_eq!(
+            serde_json::to_value(dispatched.acknowledgement()).unwrap(),
+            serde_json::json!({
+                "returnCode": "FAIL",
+                "returnMessage": "refund 123289163323899904 failed, storage unavailable",
+            })
+        );
+
+        let dispatched = NotificationRouter::new()
+            .dispatch(Notification::try_from(REFUND).unwrap())
+            .await;
+        assert_eq!(dispatched.handled, 0);
+        assert!(dispatched.is_success());
+    }
+}
@@ -7,34 +7,86 @@
 # fn main() -> Result<()>{
 # let headers = HeaderMap::new();
 # let body = "";
-let verifier = Verifier {
-    cert_serial: "Certificate's md5 hash goes here".to_string(),
-    cert_public: "Certificate's public key goes here".to_string(),
-};
+let verifier = Verifier::new(
+    "Certificate's public key goes here".to_string(),
+    "Certificate's md5 hash goes here".to_string(),
+)?;
 verifier.verify(&headers, &body)?;
 # Ok(())
 # }
 ```
 */
 
-use crate::errors::Error;
-use crate::errors::Result;
-use reqwest::header::HeaderMap;
-use ring::signature;
+use super::headers::HeaderSource;
+use ring::signature::{self, RsaPublicKeyComponents};
 use rsa_der::public_key_from_der;
+use std::borrow::Cow;
+use thiserror::Error;
+
+/// Reason why a webhook request was rejected.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum VerificationError {
+    #[error("could not find {0} in headers")]
+    MissingHeader(String),
+
+    #[error("header {0} is not valid ASCII")]
+    InvalidHeader(String),
+
+    #[error("signature is not valid base64: {0}")]
+    BadBase64(#[from] base64::DecodeError),
+
+    #[error("certificate serial {received} does not match {expected}")]
+    SerialMismatch { expected: String, received: String },
+
+    #[error("unknown certificate serial {0}")]
+    UnknownSerial(String),
+
+    #[error("bad certificate: {0}")]
+    BadCertificate(String),
+
+    #[error("signature verification failed")]
+    SignatureMismatch,
+
+    #[error("timestamp {0} is outside the accepted window")]
+    Stale(String),
+
+    #[error("nonce {0} was already used")]
+    Replayed(String),
+}
+
+impl VerificationError {
+    /// Status code to reply with, `400` for a malformed request and `401` for a request which
+    /// isn't signed by Binance.
+    pub fn http_status(&self) -> u16 {
+        match self {
+            VerificationError::MissingHeader(_)
+            | VerificationError::InvalidHeader(_)
+            | VerificationError::BadBase64(_) => 400,
+            _ => 401,
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, VerificationError>;
 
 /// Helper struct used to verify the signature of a request.
+/// The public key is parsed once, when the verifier is built.
+#[derive(Debug, Clone)]
 pub struct Verifier {
-    pub cert_public: String,
-    pub cert_serial: String,
+    cert_public: String,
+    cert_serial: String,
+    public_key: RsaPublicKeyComponents<Vec<u8>>,
 }
 
 impl<'a> Verifier {
-    pub fn new(certificate: String, cert_serial: String) -> Self {
-        Self {
+    /// Fails with [`VerificationError::BadCertificate`] if the public key can't be parsed.
+    pub fn new(certificate: String, cert_serial: String) -> Result<Self> {
+        let (n, e) = public_key_from_pem(&certificate)?;
+        Ok(Self {
             cert_public: certificate,
             cert_serial,
-        }
+            public_key: RsaPublicKeyComponents { n, e },
+        })
     }
 }
 
@@ -46,6 +98,10 @@ impl Verify<'_> for Verifier {
     fn get_certificate_serial(&self) -> &str {
         &self.cert_serial
     }
+
+    fn get_public_key(&self) -> Result<Cow<'_, RsaPublicKeyComponents<Vec<u8>>>> {
+        Ok(Cow::Borrowed(&self.public_key))
+    }
 }
 
 /// This trait needs to be implemented in order to verify the binance webhook request.
@@ -54,130 +110,121 @@ pub trait Verify<'a> {
 
     fn get_certificate_serial(&self) -> &str;
 
-    fn get_timestamp(&self, headers: &'a HeaderMap) -> Result<&'a str> {
+    fn get_timestamp<H: HeaderSource + ?Sized>(&self, headers: &'a H) -> Result<&'a str> {
         self.get_header_val("binancepay-timestamp", headers)
     }
 
-    fn get_nonce(&self, headers: &'a HeaderMap) -> Result<&'a str> {
+    fn get_nonce<H: HeaderSource + ?Sized>(&self, headers: &'a H) -> Result<&'a str> {
         self.get_header_val("binancepay-nonce", headers)
     }
 
-    fn get_public_key_md5(&self, headers: &'a HeaderMap) -> Result<&'a str> {
+    fn get_public_key_md5<H: HeaderSource + ?Sized>(&self, headers: &'a H) -> Result<&'a str> {
         self.get_header_val("binancepay-certificate-sn", headers)
     }
 
-    fn get_signature(&self, headers: &'a HeaderMap) -> Result<&'a str> {
+    fn get_signature<H: HeaderSource + ?Sized>(&self, headers: &'a H) -> Result<&'a str> {
         self.get_header_val("binancepay-signature", headers)
     }
 
-    fn get_header_val(&self, key: &str, headers: &'a HeaderMap) -> Result<&'a str> {
-        Ok(headers
-            .get(key)
-            .expect(&format!("Could not find {key} in headers"))
-            .to_str()?)
+    fn get_header_val<H: HeaderSource + ?Sized>(
+        &self,
+        key: &str,
+        headers: &'a H,
+    ) -> Result<&'a str> {
+        headers.header_str(key)
     }
 
-    fn prep_payload(&self, headers: &'a HeaderMap, body: &str) -> Result<String> {
-        Ok(format!(
-            "{}\n{}\n{}\n",
+    /// The signed payload, the body is kept byte for byte as received.
+    fn prep_payload<H: HeaderSource + ?Sized>(
+        &self,
+        headers: &'a H,
+        body: &[u8],
+    ) -> Result<Vec<u8>> {
+        let mut payload = format!(
+            "{}\n{}\n",
             self.get_timestamp(headers)?,
-            self.get_nonce(headers)?,
-            body
-        ))
+            self.get_nonce(headers)?
+        )
+        .into_bytes();
+        payload.extend_from_slice(body);
+        payload.push(b'\n');
+        Ok(payload)
     }
 
-    fn get_decoded_signature(&self, headers: &'a HeaderMap) -> Result<Vec<u8>> {
+    fn get_decoded_signature<H: HeaderSource + ?Sized>(&self, headers: &'a H) -> Result<Vec<u8>> {
         Ok(base64::decode(self.get_signature(headers)?)?)
     }
 
     /// The certificate obtained from the api is in the PEM format.
     /// This function decodes the PEM certificate contents to der format.
     fn get_decoded_certificate(&self) -> Result<Vec<u8>> {
-        Ok(base64::decode(&self.get_parsed_certificate())?)
+        decode_pem(self.get_certificate())
     }
 
     /// Extracts the public key from the der certificate format.
     fn get_public_key_from_der(&self) -> Result<(Vec<u8>, Vec<u8>)> {
-        Ok(public_key_from_der(&self.get_decoded_certificate()?)?)
+        public_key_from_pem(self.get_certificate())
     }
 
-    /// Verifies the signature of the request.
-    fn verify(&self, headers: &'a HeaderMap, body: &str) -> Result<()> {
-        assert_eq!(
-            self.get_public_key_md5(headers)?,
-            self.get_certificate_serial()
-        );
-        let der_decoded = self.get_public_key_from_der()?;
-        Ok(signature::RsaPublicKeyComponents {
-            n: der_decoded.0,
-            e: der_decoded.1,
+    /// Public key the signatures are checked with, parsed from the certificate on every call
+    /// unless the implementor keeps it around.
+    fn get_public_key(&self) -> Result<Cow<'_, RsaPublicKeyComponents<Vec<u8>>>> {
+        let (n, e) = self.get_public_key_from_der()?;
+        Ok(Cow::Owned(RsaPublicKeyComponents { n, e }))
+    }
+
+    /// Verifies the signature of the request, `body` is the raw body, e.g. `&[u8]` or `&str`.
+    fn verify<H: HeaderSource + ?Sized>(
+        &self,
+        headers: &'a H,
+        body: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let serial = self.get_public_key_md5(headers)?;
+        if serial != self.get_certificate_serial() {
+            return Err(VerificationError::SerialMismatch {
+                expected: self.get_certificate_serial().into(),
+                received: serial.into(),
+            });
         }
-        .verify(
-            &signature::RSA_PKCS1_2048_8192_SHA256,
-            self.prep_payload(headers, body)?.as_bytes(),
-            &self.get_decoded_signature(headers)?,
-        )
-        .map_err(|_| Error::Msg("Signature verification failed".into()))?)
+        self.get_public_key()?
+            .verify(
+                &signature::RSA_PKCS1_2048_8192_SHA256,
+                &self.prep_payload(headers, body.as_ref())?,
+                &self.get_decoded_signature(headers)?,
+            )
+            .map_err(|_| VerificationError::SignatureMismatch)
     }
 
     /// Get the PEM certificate contents.
     fn get_parsed_certificate(&self) -> String {
-        self.get_certificate()
-            .split('\n')
-            .filter(|line| !line.starts_with("-----"))
-            .collect::<Vec<&str>>()
-            .join("")
+        pem_contents(self.get_certificate())
     }
 }
 
+fn pem_contents(certificate: &str) -> String {
+    certificate
+        .split('\n')
+        .filter(|line| !line.starts_with("-----"))
+        .collect::<Vec<&str>>()
+        .join("")
+}
+
+fn decode_pem(certificate: &str) -> Result<Vec<u8>> {
+    base64::decode(pem_contents(certificate))
+        .map_err(|e| VerificationError::BadCertificate(e.to_string()))
+}
+
+fn public_key_from_pem(certificate: &str) -> Result<(Vec<u8>, Vec<u8>)> {
+    public_key_from_der(&decode_pem(certificate)?)
+        .map_err(|e| VerificationError::BadCertificate(e.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use reqwest::header::HeaderValue;
-    use ring::rand;
-    use ring::signature;
-    use serde::Deserialize;
-    use std::fs::read_to_string;
-
-    #[derive(Deserialize, Debug)]
-    pub struct TestData {
-        private_key: String,
-        cert_public: String,
-        cert_public_md5_hash: String,
-    }
-
-    pub fn read_test_data() -> TestData {
-        let raw_data = read_to_string("data/key_pair.json").unwrap();
-        serde_json::from_str(&raw_data).unwrap()
-    }
-
-    pub(crate) fn get_encoded_signature(
-        nonce: &str,
-        body: &str,
-        timestamp: &str,
-        private_key: &str,
-    ) -> String {
-        let payload = format!("{}\n{}\n{}\n", timestamp, nonce, body);
-        let parsed_private_key = private_key
-            .split('\n')
-            .filter(|line| !line.starts_with("-----"))
-            .collect::<Vec<&str>>()
-            .join("");
-
-        let decoded_priv_key = base64::decode(parsed_private_key).unwrap();
-        let private_key_from_der = signature::RsaKeyPair::from_der(&decoded_priv_key).unwrap();
-        let rng = rand::SystemRandom::new();
-        let mut signature = vec![0; private_key_from_der.public_modulus_len()];
-        private_key_from_der
-            .sign(
-                &signature::RSA_PKCS1_SHA256,
-                &rng,
-                payload.as_bytes(),
-                &mut signature,
-            )
-            .unwrap();
-        base64::encode(signature)
-    }
+    use crate::c2b::webhook::headers::CaseInsensitiveHeaders;
+    use crate::testing::KeyPair;
+    use reqwest::header::{HeaderMap, HeaderValue};
 
     #[test]
     fn check_signature_verification() {
@@ -186,35 +233,79 @@ mod tests {
         let nonce = "NldzYKVJuiwjCHQGlaZfwnbGaFLPimYH";
         let body = r#"{"env":{"terminalType":"WEB"},"merchantTradeNo":"9825382937292","orderAmount":25.0,"currency":"USDT","goods":{"goodsType":"02","goodsCategory":"D000","referenceGoodsId":"7876763A3B","goodsName":"Ice Cream","goodsDetail":"Greentea ice cream cone"}}"#;
         let malformed_body = "malformed_body";
-        let test_data = read_test_data();
-        let mut headers: HeaderMap = HeaderMap::new();
-        let header_keys = [
-            "BinancePay-Timestamp",
-            "BinancePay-Nonce",
-            "BinancePay-Certificate-SN",
-            "BinancePay-Signature",
-        ];
-        let encoded_signature =
-            get_encoded_signature(nonce, body, &timestamp.to_string(), &test_data.private_key);
-        let header_vals = [
-            timestamp.as_str(),
-            nonce,
-            &test_data.cert_public_md5_hash,
-            &encoded_signature,
-        ];
-
-        for (key, val) in header_keys.iter().zip(header_vals.iter()) {
-            headers.insert(*key, HeaderValue::from_str(*val).unwrap());
-        }
-        let v = Verifier {
-            cert_serial: test_data.cert_public_md5_hash,
-            cert_public: test_data.cert_public,
-        };
+        let signer = KeyPair::bundled().unwrap().signer().unwrap();
+        let headers = signer
+            .sign_with(timestamp, nonce, body)
+            .unwrap()
+            .header_map()
+            .unwrap();
+        let v = signer.verifier().unwrap();
         v.verify(&headers, body).unwrap();
         let occured = v.verify(&headers, malformed_body).unwrap_err();
-        match occured {
-            Error::Msg(e) => assert_eq!(e, "Signature verification failed"),
-            _ => assert!(false),
-        }
+        assert_eq!(occured, VerificationError::SignatureMismatch);
+        assert_eq!(occured.http_status(), 401);
+    }
+
+    #[test]
+    fn test_malformed_requests_are_rejected() {
+        let certificate = KeyPair::bundled().unwrap().certificate();
+        let v = Verifier::new(certificate.cert_public, certificate.cert_serial.clone()).unwrap();
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            v.verify(&headers, "").unwrap_err(),
+            VerificationError::MissingHeader("binancepay-certificate-sn".into())
+        );
+
+        headers.insert(
+            "BinancePay-Certificate-SN",
+            HeaderValue::from_static("other"),
+        );
+        assert!(matches!(
+            v.verify(&headers, "").unwrap_err(),
+            VerificationError::SerialMismatch { .. }
+        ));
+
+        headers.insert(
+            "BinancePay-Certificate-SN",
+            HeaderValue::from_str(&certificate.cert_serial).unwrap(),
+        );
+        headers.insert("BinancePay-Timestamp", HeaderValue::from_static("1"));
+        headers.insert("BinancePay-Nonce", HeaderValue::from_static("nonce"));
+        headers.insert("BinancePay-Signature", HeaderValue::from_static("%%%"));
+        let error = v.verify(&headers, "").unwrap_err();
+        assert!(matches!(error, VerificationError::BadBase64(_)));
+        assert_eq!(error.http_status(), 400);
+
+        assert!(matches!(
+            Verifier::new("not a certificate".into(), certificate.cert_serial),
+            Err(VerificationError::BadCertificate(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_with_any_header_source() {
+        let signer = KeyPair::bundled().unwrap().signer().unwrap();
+        let v = signer.verifier().unwrap();
+        let body = "{\"goodsName\":\"Crème brûlée\"}".as_bytes();
+        let signed = signer
+            .sign_with(
+                "1646584911979",
+                "NldzYKVJuiwjCHQGlaZfwnbGaFLPimYH",
+                std::str::from_utf8(body).unwrap(),
+            )
+            .unwrap();
+        // Header names as sent by different servers and frameworks.
+        let mut headers: Vec<(String, String)> = signed.headers;
+        headers[0].0 = "binancepay-timestamp".into();
+        headers[1].0 = "BINANCEPAY-NONCE".into();
+        v.verify(&headers, body).unwrap();
+        v.verify(&headers[..], body).unwrap();
+
+        let headers: CaseInsensitiveHeaders = headers.into_iter().collect();
+        v.verify(&headers, body).unwrap();
+        assert_eq!(
+            v.verify(&headers, &body[1..]).unwrap_err(),
+            VerificationError::SignatureMismatch
+        );
     }
 }
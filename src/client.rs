@@ -3,28 +3,31 @@ use crate::api;
 use crate::errors::BinanceContentError;
 use crate::errors::Error;
 use crate::errors::Result;
+use crate::retry::RetryPolicy;
+use crate::transport::{HttpRequest, HttpResponse, ReqwestTransport, Transport};
 use crate::utils;
-use reqwest::header::HeaderMap;
-use reqwest::header::HeaderValue;
-use reqwest::header::CONTENT_TYPE;
-use reqwest::Response;
-use reqwest::StatusCode;
-use reqwest::Url;
 use ring::hmac as rhmac;
 use serde::de::DeserializeOwned;
 use serde_json::from_str;
 use std::env;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+const DEFAULT_HOST: &str = "https://bpay.binanceapi.com";
 
 /// A client that handles all the requests made to the Binance Pay API.
+#[derive(Clone)]
 pub struct Client {
     api_key: String,
     secret_key: String,
     host: Url,
-    inner_client: reqwest::Client,
+    transport: Arc<dyn Transport>,
+    retry_policy: RetryPolicy,
 }
 
-struct RequestContent {
+pub(crate) struct RequestContent {
     timestamp: u128,
     nonce: String,
     body: Option<String>,
@@ -41,6 +44,16 @@ impl Default for RequestContent {
 }
 
 impl RequestContent {
+    /// Rebuilds the content of an already sent request, e.g. to check its signature.
+    #[cfg(any(test, feature = "sandbox"))]
+    pub(crate) fn new(timestamp: u128, nonce: String, body: Option<String>) -> Self {
+        Self {
+            timestamp,
+            nonce,
+            body,
+        }
+    }
+
     pub fn from_body(body: Option<String>) -> Self {
         Self {
             body,
@@ -72,27 +85,185 @@ impl RequestContent {
         )
     }
 
-    fn sign(&self, api_secret: &str) -> String {
+    pub(crate) fn sign(&self, api_secret: &str) -> String {
         let key = rhmac::Key::new(rhmac::HMAC_SHA512, api_secret.as_bytes());
         let raw_signature = rhmac::sign(&key, self.signature_payload().as_bytes());
         hex::encode_upper(raw_signature.as_ref())
     }
 }
 
+/// Configures the HTTP side of a [`Client`], e.g. timeouts or a proxy.
+/// Invalid settings are reported by [`ClientBuilder::build`] instead of panicking.
+/// ```
+/// # use bpay::client::Client;
+/// # use std::time::Duration;
+/// # fn main() -> bpay::errors::Result<()> {
+/// let client = Client::builder()
+///     .credentials("api_key", "api_secret")
+///     .connect_timeout(Duration::from_secs(2))
+///     .timeout(Duration::from_secs(10))
+///     .proxy("http://proxy.internal:3128")
+///     .user_agent("my-service/1.0")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct ClientBuilder {
+    api_key: Option<String>,
+    secret_key: Option<String>,
+    host: Option<String>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    proxy: Option<String>,
+    root_certificates: Vec<Vec<u8>>,
+    user_agent: Option<String>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
+    transport: Option<Arc<dyn Transport>>,
+    retry_policy: Option<RetryPolicy>,
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Api key and secret used to sign the requests.
+    pub fn credentials(
+        mut self,
+        api_key: impl Into<String>,
+        secret_key: impl Into<String>,
+    ) -> Self {
+        self.api_key = Some(api_key.into());
+        self.secret_key = Some(secret_key.into());
+        self
+    }
+
+    /// Defaults to `https://bpay.binanceapi.com`.
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// Timeout for establishing the connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for the whole request, from connecting until the body has been read.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Routes every request through the proxy, e.g. `http://host:port` or `socks5://host:port`.
+    pub fn proxy(mut self, proxy_url: impl Into<String>) -> Self {
+        self.proxy = Some(proxy_url.into());
+        self
+    }
+
+    /// Trusts an additional PEM encoded root certificate.
+    pub fn add_root_certificate_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Maximum number of idle connections kept per host.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// How long idle connections are kept, they are never closed by default.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Uses an already configured client, the HTTP settings of the builder are then ignored.
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.transport = Some(Arc::new(ReqwestTransport::new(client)));
+        self
+    }
+
+    /// Sends the requests through a custom [`Transport`],
+    /// the HTTP settings of the builder are then ignored.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    pub fn build(self) -> Result<Client> {
+        let host = Url::from_str(self.host.as_deref().unwrap_or(DEFAULT_HOST))?;
+        let transport = match self.transport {
+            Some(transport) => transport,
+            None => {
+                let mut builder =
+                    reqwest::Client::builder().pool_idle_timeout(self.pool_idle_timeout);
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(proxy) = self.proxy {
+                    builder = builder.proxy(reqwest::Proxy::all(proxy.as_str())?);
+                }
+                for pem in self.root_certificates {
+                    builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&pem)?);
+                }
+                if let Some(user_agent) = self.user_agent {
+                    builder = builder.user_agent(user_agent);
+                }
+                if let Some(max) = self.pool_max_idle_per_host {
+                    builder = builder.pool_max_idle_per_host(max);
+                }
+                Arc::new(ReqwestTransport::new(builder.build()?))
+            }
+        };
+        Ok(Client {
+            api_key: self.api_key.unwrap_or_default(),
+            secret_key: self.secret_key.unwrap_or_default(),
+            host,
+            transport,
+            retry_policy: self.retry_policy.unwrap_or_default(),
+        })
+    }
+}
+
 impl Client {
     /// Returns a client based on the specified host and credentials
     /// Credentials do not need to be specified when using public endpoints
     /// Host is mandatory
+    ///
+    /// Panics if the host is not a valid url, use [`Client::builder`] to handle it.
     pub fn new(api_key: Option<String>, secret_key: Option<String>, host: String) -> Self {
-        Self {
-            api_key: api_key.unwrap_or_else(|| "".into()),
-            secret_key: secret_key.unwrap_or_else(|| "".into()),
-            host: Url::from_str(&host).unwrap(),
-            inner_client: reqwest::Client::builder()
-                .pool_idle_timeout(None)
-                .build()
-                .unwrap(),
-        }
+        let mut builder = Self::builder().host(host);
+        builder.api_key = api_key;
+        builder.secret_key = secret_key;
+        builder.build().unwrap()
+    }
+
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Replaces the [`RetryPolicy`] applied to the signed requests.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
     }
 
     /// Grabs the BINANCE_PAY_API_KEY, BINANCE_PAY_API_SECRET, BINANCE_HOST from the environment variables
@@ -102,8 +273,7 @@ impl Client {
             env::var("BINANCE_PAY_API_KEY").expect("BINANCE_PAY_API_KEY env var not set.");
         let secret_key =
             env::var("BINANCE_PAY_API_SECRET").expect("BINANCE_PAY_API_SECRET env var not set.");
-        let host =
-            env::var("BINANCE_HOST").unwrap_or_else(|_| "https://bpay.binanceapi.com".into());
+        let host = env::var("BINANCE_HOST").unwrap_or_else(|_| DEFAULT_HOST.into());
         Self::new(Some(api_key), Some(secret_key), host)
     }
 
@@ -121,6 +291,8 @@ impl Client {
 
     /// Performs a signed POST request to the specified endpoint,
     /// with the specified body as Serializable struct.
+    /// Returns the `data` of the response envelope, a `FAIL` envelope is turned into
+    /// [`Error::BinanceError`] the same way a `400` response is.
     pub async fn post_signed_s<T: DeserializeOwned, S: serde::Serialize>(
         &self,
         endpoint: api::API,
@@ -136,12 +308,36 @@ impl Client {
         } else {
             "".to_string()
         };
-        self.post_signed_de(endpoint, Some(request_str)).await
+        self.post_signed_de::<api::Response<T>>(endpoint, Some(request_str))
+            .await?
+            .into_result()
     }
 
     /// Performs a signed POST request to the specified endpoint,
     /// with the specified body as String.
+    /// Failed attempts are retried as configured by the client's [`RetryPolicy`],
+    /// every attempt is signed with a fresh nonce and timestamp.
     pub async fn post_signed(&self, endpoint: api::API, request: Option<String>) -> Result<String> {
+        let attempts = self.retry_policy.attempts_for(endpoint);
+        let mut attempt = 1;
+        loop {
+            match self.post_signed_once(endpoint, request.clone()).await {
+                Err(e) if attempt < attempts && e.is_retryable() => {
+                    let backoff = self.retry_policy.backoff(attempt);
+                    log::warn!("Attempt {attempt} failed with {e}, retrying in {backoff:?}");
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn post_signed_once(
+        &self,
+        endpoint: api::API,
+        request: Option<String>,
+    ) -> Result<String> {
         let request_content = RequestContent::from_body(request);
         let payload_signature = request_content.sign(&self.secret_key);
         let payload = request_content.get_body();
@@ -149,7 +345,7 @@ impl Client {
             request_content.get_timestamp(),
             request_content.get_nonce(),
             payload_signature.as_str(),
-        )?;
+        );
         self.post(endpoint, payload.to_string(), headers).await
     }
 
@@ -159,36 +355,28 @@ impl Client {
         &self,
         endpoint: api::API,
         body: String,
-        headers: HeaderMap<HeaderValue>,
+        headers: Vec<(String, String)>,
     ) -> Result<String> {
-        let url = self.host.join(&String::from(endpoint)).unwrap();
-        let response = self
-            .inner_client
-            .post(url)
-            .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-        self.handler(response).await
+        let request = HttpRequest {
+            url: self.host.join(&String::from(endpoint))?,
+            headers,
+            body,
+        };
+        let response = self.transport.send(request).await?;
+        self.handler(response)
     }
 
     /// Process the reponse from the request and returns the body as String
     /// or returns an error if the status code is not 200
-    async fn handler(&self, response: Response) -> Result<String> {
-        match response.status() {
-            StatusCode::OK => {
-                let body = response.bytes().await?;
-                let result = std::str::from_utf8(&body);
-                Ok(result?.to_string())
-            }
-            StatusCode::INTERNAL_SERVER_ERROR => Err(Error::InternalServerError),
-            StatusCode::SERVICE_UNAVAILABLE => Err(Error::ServiceUnavailable),
-            StatusCode::UNAUTHORIZED => Err(Error::Unauthorized),
-            StatusCode::BAD_REQUEST => {
-                let error: BinanceContentError = response.json().await?;
-                Err(match (error.code, &error.error_message) {
-                    _ => Error::BinanceError { response: error },
-                })
+    fn handler(&self, response: HttpResponse) -> Result<String> {
+        match response.status {
+            200 => Ok(std::str::from_utf8(&response.body)?.to_string()),
+            500 => Err(Error::InternalServerError),
+            503 => Err(Error::ServiceUnavailable),
+            401 => Err(Error::Unauthorized),
+            400 => {
+                let error: BinanceContentError = serde_json::from_slice(&response.body)?;
+                Err(Error::BinanceError { response: error })
             }
             s => Err(Error::Msg(format!("Received response: {:?}", s))),
         }
@@ -196,21 +384,19 @@ impl Client {
 
     /// Prepare a header map as per API specification common rules laid by Binance.
     /// [Required Headers](https://developers.binance.com/docs/binance-pay/api-common#request-header)
-    fn build_headers(&self, timestamp: u128, nonce: &str, signature: &str) -> Result<HeaderMap> {
-        let header_keys = [
-            "BinancePay-Timestamp",
-            "BinancePay-Nonce",
-            "BinancePay-Certificate-SN",
-            "BinancePay-Signature",
-        ];
-        let timestamp = timestamp.to_string();
-        let header_vals = [timestamp.as_str(), nonce, self.api_key.as_str(), signature];
-        let mut headers = HeaderMap::new();
-        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
-        for i in 0..header_keys.len() {
-            headers.insert(header_keys[i], HeaderValue::from_str(header_vals[i])?);
-        }
-        Ok(headers)
+    fn build_headers(
+        &self,
+        timestamp: u128,
+        nonce: &str,
+        signature: &str,
+    ) -> Vec<(String, String)> {
+        vec![
+            ("Content-Type".into(), "application/json".into()),
+            ("BinancePay-Timestamp".into(), timestamp.to_string()),
+            ("BinancePay-Nonce".into(), nonce.into()),
+            ("BinancePay-Certificate-SN".into(), self.api_key.clone()),
+            ("BinancePay-Signature".into(), signature.into()),
+        ]
     }
 }
 
@@ -218,6 +404,10 @@ impl Client {
 mod tests {
 
     use super::*;
+    use crate::errors::ErrorCode;
+    use crate::transport::InMemoryTransport;
+    use mockito::mock;
+    use std::sync::atomic::{AtomicU32, Ordering};
 
     #[test]
     fn check_signature_algo() {
@@ -229,4 +419,178 @@ mod tests {
         };
         assert_eq!(&rc.sign(secret_key), "0FEE450C836654F95CA8AC5B99DB385B96CAE1EDC46456A5BEA005BFA020FC113AD61D9B8595BA951A3A562BBB8556B6D063D6BA8AEF488097642E50ACC27ACA")
     }
+
+    #[test]
+    fn test_builder_reports_invalid_config() {
+        assert!(matches!(
+            Client::builder().host("not a url").build(),
+            Err(Error::UrlParseError(_))
+        ));
+        assert!(matches!(
+            Client::builder().proxy("::invalid proxy::").build(),
+            Err(Error::ReqError(_))
+        ));
+        assert!(Client::builder()
+            .add_root_certificate_pem("not a certificate")
+            .build()
+            .is_err());
+        assert!(Client::builder()
+            .credentials("api_key", "api_secret")
+            .host("http://localhost:8080")
+            .connect_timeout(Duration::from_secs(1))
+            .timeout(Duration::from_secs(5))
+            .proxy("socks5://127.0.0.1:1080")
+            .user_agent("bpay-test")
+            .pool_max_idle_per_host(2)
+            .pool_idle_timeout(Duration::from_secs(30))
+            .build()
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_signed_request_over_custom_transport() {
+        let transport = Arc::new(InMemoryTransport::new(|_| {
+            HttpResponse::ok(r#"{"status":"SUCCESS","code":"000000","data":true}"#)
+        }));
+        let client = Client::builder()
+            .credentials("api_key", "abcd1234")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let closed: bool = client
+            .post_signed_s(api::API::CloseOrder, Some(&r#"{"prepayId":"1"}"#))
+            .await
+            .unwrap();
+        assert!(closed);
+
+        let request = &transport.requests()[0];
+        assert_eq!(
+            request.url.as_str(),
+            "https://bpay.binanceapi.com/binancepay/openapi/order/close"
+        );
+        assert_eq!(request.header("binancepay-certificate-sn"), Some("api_key"));
+        let content = RequestContent::new(
+            request
+                .header("BinancePay-Timestamp")
+                .unwrap()
+                .parse()
+                .unwrap(),
+            request.header("BinancePay-Nonce").unwrap().to_string(),
+            Some(request.body.clone()),
+        );
+        assert_eq!(
+            request.header("BinancePay-Signature").unwrap(),
+            content.sign("abcd1234")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request_is_not_sent() {
+        let transport = Arc::new(InMemoryTransport::new(|_| {
+            HttpResponse::ok(r#"{"status":"SUCCESS","code":"000000","data":true}"#)
+        }));
+        let client = Client::builder()
+            .credentials("api_key", "abcd1234")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let error = api::order::close::Request::new(None, None)
+            .close(&client)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Validation(_)));
+        assert!(transport.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fail_envelope_with_ok_status() {
+        let _m = mock("POST", "/binancepay/openapi/order/query")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status":"FAIL","code":"400202","errorMessage":"Order not found."}"#)
+            .create();
+        let client = Client::new(None, None, mockito::server_url());
+        let error = client
+            .post_signed_s::<serde_json::Value, String>(api::API::QueryOrder, None)
+            .await
+            .unwrap_err();
+        match error {
+            Error::BinanceError { response } => {
+                assert_eq!(response.code, ErrorCode::OrderNotFound);
+                assert_eq!(response.error_message, "Order not found.");
+            }
+            e => panic!("Unexpected error: {e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_idempotent_endpoints_only() {
+        let client = Client::new(None, None, mockito::server_url()).with_retry_policy(
+            RetryPolicy::default()
+                .max_attempts(3)
+                .initial_backoff(std::time::Duration::ZERO),
+        );
+
+        let query = mock("POST", "/binancepay/openapi/balance")
+            .with_status(503)
+            .expect(3)
+            .create();
+        let error = client
+            .post_signed(api::API::BalanceQuery, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::ServiceUnavailable));
+        query.assert();
+
+        let create = mock("POST", "/binancepay/openapi/v2/order")
+            .with_status(503)
+            .expect(1)
+            .create();
+        client
+            .post_signed(api::API::CreateOrder, None)
+            .await
+            .unwrap_err();
+        create.assert();
+    }
+
+    #[tokio::test]
+    async fn test_permanent_transport_errors_are_not_retried() {
+        struct FailingTransport {
+            retryable: bool,
+            attempts: AtomicU32,
+        }
+
+        #[async_trait::async_trait]
+        impl Transport for FailingTransport {
+            async fn send(&self, _: HttpRequest) -> Result<HttpResponse> {
+                self.attempts.fetch_add(1, Ordering::SeqCst);
+                Err(Error::Transport {
+                    source: "connection failed".into(),
+                    retryable: self.retryable,
+                })
+            }
+        }
+
+        for (retryable, attempts) in [(false, 1), (true, 3)] {
+            let transport = Arc::new(FailingTransport {
+                retryable,
+                attempts: AtomicU32::new(0),
+            });
+            let client = Client::builder()
+                .transport(transport.clone())
+                .retry_policy(
+                    RetryPolicy::default()
+                        .max_attempts(3)
+                        .initial_backoff(std::time::Duration::ZERO),
+                )
+                .build()
+                .unwrap();
+            let error = client
+                .post_signed(api::API::BalanceQuery, None)
+                .await
+                .unwrap_err();
+            assert!(matches!(error, Error::Transport { .. }));
+            assert_eq!(transport.attempts.load(Ordering::SeqCst), attempts);
+        }
+    }
 }
@@ -1,13 +1,149 @@
 //! Custom error handling for the crate specific errrors.
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
+use std::fmt;
 use thiserror::Error;
 
+/// Broad classification of the Binance Pay error codes,
+/// lets the caller decide how to react without matching on messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// Temporary failure, the same request may succeed later.
+    Retryable,
+
+    /// The request content is malformed or refers to something that doesn't exist.
+    InvalidParameter,
+
+    /// Credentials, signature or merchant account related failure.
+    Auth,
+
+    /// The wallet does not hold enough funds.
+    InsufficientBalance,
+
+    /// The request id or merchant trade no was already used.
+    Duplicate,
+
+    /// The code is not part of the catalog.
+    Unknown,
+}
+
+/// Declares the error code catalog as (variant, code, category) triples.
+macro_rules! error_codes {
+    (
+        $($(#[$doc: meta])* ($variant: ident, $code: literal, $category: ident)),* $(,)?
+    ) => {
+        /// Error codes documented by Binance Pay.
+        /// [Error Codes](https://developers.binance.com/docs/binance-pay/api-common#error-code)
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum ErrorCode {
+            $($(#[$doc])* $variant,)*
+
+            /// Any code which is not part of the catalog yet.
+            Unknown(u32),
+        }
+
+        impl ErrorCode {
+            /// Numeric value of the code as sent by Binance.
+            pub fn code(&self) -> u32 {
+                match self {
+                    $(ErrorCode::$variant => $code,)*
+                    ErrorCode::Unknown(code) => *code,
+                }
+            }
+
+            pub fn category(&self) -> ErrorCategory {
+                match self {
+                    $(ErrorCode::$variant => ErrorCategory::$category,)*
+                    ErrorCode::Unknown(_) => ErrorCategory::Unknown,
+                }
+            }
+        }
+
+        impl From<u32> for ErrorCode {
+            fn from(code: u32) -> Self {
+                match code {
+                    $($code => ErrorCode::$variant,)*
+                    code => ErrorCode::Unknown(code),
+                }
+            }
+        }
+    };
+}
+
+error_codes!(
+    /// An unknown error occurred while processing the request.
+    (UnknownError, 400000, Retryable),
+    /// Parameter format is wrong or parameter transferring doesn't follow the rules.
+    (InvalidRequest, 400001, InvalidParameter),
+    /// Incorrect signature result.
+    (InvalidSignature, 400002, Auth),
+    /// Timestamp for this request is outside of the time window.
+    (InvalidTimestamp, 400003, Retryable),
+    /// API identity key not found or invalid.
+    (InvalidApiKeyOrIp, 400004, Auth),
+    /// API identity key format invalid.
+    (BadApiKeyFmt, 400005, Auth),
+    /// Mandatory parameter missing or malformed.
+    (MandatoryParamEmptyOrMalformed, 400006, InvalidParameter),
+    /// Parameter has a wrong length.
+    (InvalidParamWrongLength, 400007, InvalidParameter),
+    /// Parameter has a wrong value.
+    (InvalidParamWrongValue, 400008, InvalidParameter),
+    /// Parameter contains illegal characters.
+    (InvalidParamIllegalChar, 400009, InvalidParameter),
+    /// Request content is too large.
+    (InvalidRequestTooLarge, 400010, InvalidParameter),
+    /// merchantTradeNo is invalid or duplicated, Binance uses it for the duplicated ones.
+    (InvalidMerchantTradeNo, 400011, Duplicate),
+    /// The merchant does not exist.
+    (MerchantNotExists, 400201, Auth),
+    /// The order does not exist.
+    (OrderNotFound, 400202, InvalidParameter),
+    /// The currency is not supported.
+    (IllegalCurrency, 400203, InvalidParameter),
+    /// The order status doesn't allow the requested action.
+    (InvalidOrderStatus, 400204, InvalidParameter),
+    /// The request id was already used.
+    (DuplicateRequest, 400210, Duplicate),
+    /// Not enough balance in the wallet.
+    (InsufficientBalance, 400604, InsufficientBalance),
+);
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// Binance sends the code as a string, e.g. `"400201"`, numbers are accepted as well.
+impl<'de> Deserialize<'de> for ErrorCode {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawCode {
+            Number(u32),
+            Text(String),
+        }
+
+        match RawCode::deserialize(deserializer)? {
+            RawCode::Number(code) => Ok(code.into()),
+            RawCode::Text(code) => code
+                .trim()
+                .parse::<u32>()
+                .map(ErrorCode::from)
+                .map_err(|_| serde::de::Error::custom(format!("invalid error code {code}"))),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Error)]
 #[serde(rename_all = "camelCase")]
 #[error("code: {code}, error_message: {error_message}")]
 pub struct BinanceContentError {
     pub status: String,
-    pub code: i16,
+    pub code: ErrorCode,
     pub error_message: String,
 }
 
@@ -31,6 +167,32 @@ pub enum Error {
     #[error(transparent)]
     Base64DecodeError(#[from] base64::DecodeError),
 
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    UrlParseError(#[from] url::ParseError),
+
+    /// Failure reported by a custom [`Transport`](crate::transport::Transport),
+    /// the request is only sent again when the transport marks it `retryable`.
+    #[error("{source}")]
+    Transport {
+        source: Box<dyn std::error::Error + Send + Sync>,
+        retryable: bool,
+    },
+
+    /// The request was rejected before being sent, see [`Validate`](crate::c2b::validation::Validate).
+    #[error(transparent)]
+    Validation(#[from] crate::c2b::validation::ValidationError),
+
+    /// The webhook request was rejected, see [`Verify`](crate::c2b::webhook::verification::Verify).
+    #[error(transparent)]
+    Verification(#[from] crate::c2b::webhook::verification::VerificationError),
+
+    #[cfg(any(test, feature = "sqlite"))]
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+
     #[error(transparent)]
     RSADerError(#[from] rsa_der::Error),
 
@@ -49,8 +211,103 @@ pub enum Error {
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error(transparent)]
+    InvalidTransition(#[from] crate::c2b::order::lifecycle::InvalidTransition),
+
+    /// Returned by the [`poll`](crate::poll) helpers when the deadline passes.
+    #[error("not in a final state after {attempts} queries in {elapsed:?}")]
+    PollTimeout {
+        attempts: u32,
+        elapsed: std::time::Duration,
+    },
+
     #[error("{0}")]
     Msg(String),
 }
 
+impl Error {
+    /// The Binance error code, if the error was reported by Binance.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Error::BinanceError { response } => Some(response.code),
+            _ => None,
+        }
+    }
+
+    /// Category of the Binance error code, if the error was reported by Binance.
+    pub fn category(&self) -> Option<ErrorCategory> {
+        self.code().map(|code| code.category())
+    }
+
+    /// Whether sending the same request again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::InternalServerError | Error::ServiceUnavailable => true,
+            Error::ReqError(e) => e.is_timeout() || e.is_connect(),
+            Error::Transport { retryable, .. } => *retryable,
+            _ => self.category() == Some(ErrorCategory::Retryable),
+        }
+    }
+}
+
 pub type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_binance_error() {
+        let error: BinanceContentError = serde_json::from_str(
+            r#"{"status":"FAIL","code":"400201","errorMessage":"Merchant does not exist."}"#,
+        )
+        .unwrap();
+        assert_eq!(error.code, ErrorCode::MerchantNotExists);
+        assert_eq!(error.code.category(), ErrorCategory::Auth);
+
+        let error: BinanceContentError = serde_json::from_str(
+            r#"{"status":"FAIL","code":400604,"errorMessage":"Insufficient balance."}"#,
+        )
+        .unwrap();
+        assert_eq!(error.code, ErrorCode::InsufficientBalance);
+        assert_eq!(
+            Error::from(error).category(),
+            Some(ErrorCategory::InsufficientBalance)
+        );
+    }
+
+    #[test]
+    fn test_unknown_error_code() {
+        let error: BinanceContentError = serde_json::from_str(
+            r#"{"status":"FAIL","code":"499999","errorMessage":"Something new."}"#,
+        )
+        .unwrap();
+        assert_eq!(error.code, ErrorCode::Unknown(499999));
+        assert_eq!(error.code.code(), 499999);
+        assert_eq!(error.code.category(), ErrorCategory::Unknown);
+        assert!(!Error::from(error).is_retryable());
+    }
+
+    #[test]
+    fn test_duplicate_errors() {
+        for code in ["400011", "400210"] {
+            let error: BinanceContentError = serde_json::from_str(&format!(
+                r#"{{"status":"FAIL","code":"{code}","errorMessage":"Duplicated."}}"#
+            ))
+            .unwrap();
+            assert_eq!(error.code.category(), ErrorCategory::Duplicate);
+        }
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        assert!(Error::ServiceUnavailable.is_retryable());
+        assert!(Error::from(BinanceContentError {
+            status: "FAIL".into(),
+            code: ErrorCode::UnknownError,
+            error_message: "".into(),
+        })
+        .is_retryable());
+        assert!(!Error::Unauthorized.is_retryable());
+    }
+}
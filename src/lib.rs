@@ -16,8 +16,9 @@ tokio = { version = "1.18.0", features = ["rt-multi-thread", "macros"] }
 
  ## Example
  ```
+use bpay::api::amount::Amount;
 use bpay::api::order::create::{
-    Currency, Env, Goods, GoodsCategory, GoodsType, Request as OrderRequest, TerminalType,
+    Currency, Goods, GoodsCategory, GoodsType, Request as OrderRequest, TerminalType,
 };
 use bpay::client::Client;
 use bpay::utils::create_nonce;
@@ -29,21 +30,18 @@ use tokio;
 #[tokio::main]
 async fn main() {
 
-    let order = OrderRequest {
-        env: Env {
-            terminal_type: TerminalType::Web,
-        },
-        merchant_trade_no: create_nonce(10),
-        order_amount: 10.0,
-        currency: Currency::USDT,
-        goods: Goods {
-            goods_type: GoodsType::VirtualGoods,
-            goods_category: GoodsCategory::Electronics,
-            reference_goods_id: "sku1234".into(),
-            goods_name: "Laptop".into(),
-            goods_detail: None,
-        },
-    };
+    let order = OrderRequest::builder()
+        .terminal_type(TerminalType::Web)
+        .merchant_trade_no(create_nonce(10))
+        .order_amount(Amount::from(10u32))
+        .currency(Currency::USDT)
+        .goods(Goods::new(
+            GoodsType::VirtualGoods,
+            GoodsCategory::Electronics,
+            "sku1234",
+            "Laptop",
+        ))
+        .build().unwrap();
 
     let client = Client::from_env();
     # let url = &mockito::server_url();
@@ -78,7 +76,16 @@ async fn main() {
  ```
 */
 pub mod api;
+#[cfg(any(test, feature = "blocking"))]
+pub mod blocking;
 pub mod c2b;
 pub mod client;
 pub mod errors;
+pub mod poll;
+pub mod retry;
+#[cfg(any(test, feature = "sandbox"))]
+pub mod sandbox;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod transport;
 pub mod utils;
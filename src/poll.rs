@@ -0,0 +1,256 @@
+//! Waits for orders, refunds, transfers and payouts to reach a final state.
+/*!
+Each helper queries the resource until its status is terminal and returns the last response.
+The interval between two queries grows by the [`PollPolicy`] multiplier, once its deadline
+passes [`Error::PollTimeout`] is returned. Failed queries are returned right away, the
+transient failures are already retried by the [`Client`].
+
+```rust,no_run
+# use bpay::api::order::query::Request;
+# use bpay::client::Client;
+# use bpay::poll::{self, PollPolicy};
+# use std::time::Duration;
+# #[tokio::main]
+# async fn main() -> bpay::errors::Result<()> {
+# let client = Client::from_env();
+let policy = PollPolicy::default()
+    .interval(Duration::from_secs(1))
+    .deadline(Duration::from_secs(15 * 60));
+let request = Request::new(Some("29383937493038367292".into()), None);
+let order = poll::order(&client, &request, &policy).await?;
+println!("{:?}", order.status);
+# Ok(())
+# }
+```
+*/
+
+use crate::api::{order, payout, refund, transfer};
+use crate::client::Client;
+use crate::errors::{Error, Result};
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug, Clone)]
+pub struct PollPolicy {
+    interval: Duration,
+    max_interval: Duration,
+    multiplier: f64,
+    deadline: Duration,
+}
+
+impl Default for PollPolicy {
+    /// Polls every 2s growing by half up to 30s, for at most 30 minutes.
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(2),
+            max_interval: Duration::from_secs(30),
+            multiplier: 1.5,
+            deadline: Duration::from_secs(30 * 60),
+        }
+    }
+}
+
+impl PollPolicy {
+    /// Wait time before the second query.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Upper bound of the wait time between two queries.
+    pub fn max_interval(mut self, max_interval: Duration) -> Self {
+        self.max_interval = max_interval;
+        self
+    }
+
+    /// Factor applied to the wait time after every query, `1.0` polls at a fixed interval.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Time after which polling gives up, measured from the first query.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Queries until `is_terminal` holds for the response.
+    pub async fn poll<T, F, Fut>(&self, mut query: F, is_terminal: impl Fn(&T) -> bool) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let start = Instant::now();
+        let mut interval = self.interval;
+        let mut attempts = 0;
+        loop {
+            let response = query().await?;
+            attempts += 1;
+            if is_terminal(&response) {
+                return Ok(response);
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= self.deadline {
+                return Err(Error::PollTimeout { attempts, elapsed });
+            }
+            tokio::time::sleep(interval.min(self.deadline - elapsed)).await;
+            interval = interval.mul_f64(self.multiplier).min(self.max_interval);
+        }
+    }
+}
+
+/// Waits for the order to reach a terminal [`order::query::Status`].
+pub async fn order(
+    client: &Client,
+    request: &order::query::Request,
+    policy: &PollPolicy,
+) -> Result<order::query::Response> {
+    policy
+        .poll(
+            || request.query(client),
+            |response: &order::query::Response| response.status.is_terminal(),
+        )
+        .await
+}
+
+/// Waits for the refund to leave [`RefundPending`](refund::query::RefundStatus::RefundPending).
+pub async fn refund(
+    client: &Client,
+    request: &refund::query::Request,
+    policy: &PollPolicy,
+) -> Result<refund::query::Response> {
+    policy
+        .poll(
+            || request.query(client),
+            |response: &refund::query::Response| response.refund_status.is_terminal(),
+        )
+        .await
+}
+
+/// Waits for the transfer to leave [`Process`](transfer::query::Status::Process).
+pub async fn transfer(
+    client: &Client,
+    request: &transfer::query::Request,
+    policy: &PollPolicy,
+) -> Result<transfer::query::Response> {
+    policy
+        .poll(
+            || request.query(client),
+            |response: &transfer::query::Response| response.status.is_terminal(),
+        )
+        .await
+}
+
+/// Waits for the payout batch to reach a final status, see [`BatchStatus::is_terminal`](payout::query::BatchStatus::is_terminal).
+pub async fn payout(
+    client: &Client,
+    request: &payout::query::Request,
+    policy: &PollPolicy,
+) -> Result<payout::query::Response> {
+    policy
+        .poll(
+            || request.query(client),
+            |response: &payout::query::Response| response.batch_status.is_terminal(),
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{HttpResponse, InMemoryTransport};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    fn transfer_client(statuses: &'static [&'static str]) -> Client {
+        let calls = AtomicU32::new(0);
+        let transport = InMemoryTransport::new(move |_| {
+            let call = calls.fetch_add(1, Ordering::SeqCst) as usize;
+            let status = statuses[call.min(statuses.len() - 1)];
+            HttpResponse::ok(format!(
+                r#"{{"status":"SUCCESS","code":"000000","data":{{"tran_id":"1","status":"{status}"}}}}"#
+            ))
+        });
+        Client::builder()
+            .transport(Arc::new(transport))
+            .build()
+            .unwrap()
+    }
+
+    fn policy() -> PollPolicy {
+        PollPolicy::default()
+            .interval(Duration::from_millis(5))
+            .multiplier(2.0)
+            .max_interval(Duration::from_millis(20))
+    }
+
+    #[tokio::test]
+    async fn test_poll_until_terminal() {
+        let client = transfer_client(&["PROCESS", "PROCESS", "SUCCESS"]);
+        let request = transfer::query::Request {
+            tran_id: "1".into(),
+        };
+        let response = transfer(&client, &request, &policy()).await.unwrap();
+        assert_eq!(response.status, transfer::query::Status::Success);
+    }
+
+    #[tokio::test]
+    async fn test_poll_timeout() {
+        let client = transfer_client(&["PROCESS"]);
+        let request = transfer::query::Request {
+            tran_id: "1".into(),
+        };
+        let policy = policy().deadline(Duration::from_millis(50));
+        match transfer(&client, &request, &policy).await {
+            Err(Error::PollTimeout { attempts, elapsed }) => {
+                assert!(attempts > 1);
+                assert!(elapsed >= Duration::from_millis(50));
+            }
+            other => panic!("expected a timeout, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_poll_order_until_paid() {
+        use crate::api::order::create::{Currency, Goods, GoodsCategory, GoodsType, TerminalType};
+        use crate::c2b::amount::Amount;
+        use crate::sandbox::Simulator;
+
+        let simulator = Simulator::new("api_key", "api_secret")
+            .start()
+            .await
+            .unwrap();
+        let client = Client::new(
+            Some("api_key".into()),
+            Some("api_secret".into()),
+            simulator.url(),
+        );
+        let created = order::create::Request::builder()
+            .terminal_type(TerminalType::Web)
+            .merchant_trade_no("9825382937292")
+            .order_amount(Amount::from(10u32))
+            .currency(Currency::USDT)
+            .goods(Goods::new(
+                GoodsType::VirtualGoods,
+                GoodsCategory::Others,
+                "sku",
+                "Gems",
+            ))
+            .build()
+            .unwrap()
+            .create(&client)
+            .await
+            .unwrap();
+
+        let request = order::query::Request::new(Some(created.prepay_id.clone()), None);
+        let policy = policy();
+        let (response, paid) = tokio::join!(order(&client, &request, &policy), async {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            simulator.pay_order(&created.prepay_id).await
+        });
+        paid.unwrap();
+        assert_eq!(response.unwrap().status, order::query::Status::Paid);
+    }
+}
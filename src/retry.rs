@@ -0,0 +1,137 @@
+//! Retry policy applied by the [`Client`](crate::client::Client) to failed requests.
+/*!
+Retries are enabled by default only for the idempotent endpoints, see [`API::is_idempotent`].
+Other endpoints have to be opted in explicitly.
+
+```
+# use bpay::api::API;
+# use bpay::client::Client;
+# use bpay::retry::RetryPolicy;
+# use std::time::Duration;
+let policy = RetryPolicy::default()
+    .max_attempts(5)
+    .initial_backoff(Duration::from_millis(100))
+    .retry_endpoint(API::CreateOrder);
+let client = Client::new(None, None, "https://bpay.binanceapi.com".into()).with_retry_policy(policy);
+```
+*/
+
+use crate::api::API;
+use rand::Rng;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: f64,
+    endpoints: Vec<API>,
+}
+
+impl Default for RetryPolicy {
+    /// Up to 3 attempts, starting with a 200ms backoff which doubles on every retry.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            jitter: 0.5,
+            endpoints: vec![],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy which sends every request exactly once.
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Total number of attempts including the first one.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Wait time before the first retry, doubled for every following one.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Upper bound of the wait time between two attempts.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Fraction of the backoff which is randomized, between `0.0` (none) and `1.0` (full jitter).
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Opts a non idempotent endpoint in, e.g. [`API::CreateOrder`].
+    /// Only do this when the request carries an id Binance deduplicates on.
+    pub fn retry_endpoint(mut self, endpoint: API) -> Self {
+        self.endpoints.push(endpoint);
+        self
+    }
+
+    /// Number of attempts allowed for a request to the endpoint.
+    pub fn attempts_for(&self, endpoint: API) -> u32 {
+        if endpoint.is_idempotent() || self.endpoints.contains(&endpoint) {
+            self.max_attempts
+        } else {
+            1
+        }
+    }
+
+    /// Wait time before the given retry, `1` being the first retry.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff);
+        if self.jitter == 0.0 {
+            return exponential;
+        }
+        let factor = 1.0 - self.jitter * rand::thread_rng().gen_range(0.0..=1.0);
+        exponential.mul_f64(factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retries_only_idempotent_endpoints_by_default() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.attempts_for(API::QueryOrder), 3);
+        assert_eq!(policy.attempts_for(API::PayoutQuery), 3);
+        assert_eq!(policy.attempts_for(API::CreateOrder), 1);
+        assert_eq!(policy.attempts_for(API::BatchPayout), 1);
+
+        let policy = policy.retry_endpoint(API::CreateOrder);
+        assert_eq!(policy.attempts_for(API::CreateOrder), 3);
+        assert_eq!(RetryPolicy::none().attempts_for(API::QueryOrder), 1);
+    }
+
+    #[test]
+    fn test_exponential_backoff() {
+        let policy = RetryPolicy::default()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(350))
+            .jitter(0.0);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+
+        let policy = policy.jitter(1.0);
+        for retry in 1..5 {
+            assert!(policy.backoff(retry) <= Duration::from_millis(350));
+        }
+    }
+}
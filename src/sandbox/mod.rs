@@ -0,0 +1,622 @@
+/*!
+Local stand-in for the Binance Pay API, used to exercise whole payment flows without network access.
+
+The simulator serves every path of [`API`], checks the `BinancePay-Signature` of each request
+with the configured secret, keeps orders, refunds, transfers and payouts in memory and emits
+RSA signed webhooks which [`Verifier`](crate::c2b::webhook::verification::Verifier) accepts.
+The payer's side of a flow is driven through [`SimulatorHandle`].
+
+```rust,no_run
+# use bpay::api::order::query::Request as QueryOrderRequest;
+# use bpay::api::order::create::{Currency, Goods, GoodsCategory, GoodsType, Request as OrderRequest, TerminalType};
+# use bpay::c2b::amount::Amount;
+# use bpay::client::Client;
+# use bpay::sandbox::Simulator;
+# #[tokio::main]
+# async fn main() -> bpay::errors::Result<()> {
+let simulator = Simulator::new("api_key", "api_secret").start().await?;
+let client = Client::new(Some("api_key".into()), Some("api_secret".into()), simulator.url());
+
+let order = OrderRequest::builder()
+    .terminal_type(TerminalType::Web)
+    .merchant_trade_no("9825382937292")
+    .order_amount(Amount::from(10u32))
+    .currency(Currency::USDT)
+    .goods(Goods::new(GoodsType::VirtualGoods, GoodsCategory::Others, "sku", "Gems"))
+    .build()?
+    .create(&client)
+    .await?;
+simulator.pay_order(&order.prepay_id).await?;
+
+let query = QueryOrderRequest::new(Some(order.prepay_id), None);
+println!("{:?}", query.query(&client).await?.status);
+# Ok(())
+# }
+```
+*/
+
+mod state;
+mod webhook;
+
+pub use crate::testing::SignedNotification as SentNotification;
+
+use crate::api::API;
+use crate::c2b::amount::Amount;
+use crate::c2b::webhook::certificate::CertificateResult;
+use crate::client::RequestContent;
+use crate::errors::{BinanceContentError, Error, ErrorCode, Result};
+use crate::testing::{KeyPair, NotificationSigner};
+use axum::body::Bytes;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Extension, Json, Router};
+use serde_json::{json, Value};
+use state::{ApiError, Reply, State};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+const ENDPOINTS: [API; 12] = [
+    API::CreateOrder,
+    API::QueryCertificate,
+    API::QueryOrder,
+    API::CloseOrder,
+    API::RefundOrder,
+    API::BalanceQuery,
+    API::TransferFund,
+    API::QueryTransfer,
+    API::QueryRefund,
+    API::BatchPayout,
+    API::CreateSubMerchant,
+    API::PayoutQuery,
+];
+
+/// Configuration of a simulator which is not running yet.
+pub struct Simulator {
+    api_key: String,
+    secret_key: String,
+    webhook_url: Option<String>,
+    balances: Vec<(String, String, Amount)>,
+}
+
+impl Simulator {
+    /// Simulator accepting requests signed with the given credentials.
+    pub fn new(api_key: impl Into<String>, secret_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            secret_key: secret_key.into(),
+            webhook_url: None,
+            balances: vec![],
+        }
+    }
+
+    /// Url the signed webhook notifications are posted to.
+    /// Without it notifications are only recorded, see [`SimulatorHandle::notifications`].
+    pub fn webhook_url(mut self, url: impl Into<String>) -> Self {
+        self.webhook_url = Some(url.into());
+        self
+    }
+
+    /// Initial balance of a wallet, e.g. `("FUNDING_WALLET", "USDT", Amount::from(100u32))`.
+    pub fn balance(
+        mut self,
+        wallet: impl Into<String>,
+        currency: impl Into<String>,
+        amount: Amount,
+    ) -> Self {
+        self.balances.push((wallet.into(), currency.into(), amount));
+        self
+    }
+
+    /// Binds the simulator to a random local port and serves it in the background
+    /// until the returned handle is dropped.
+    pub async fn start(self) -> Result<SimulatorHandle> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let mut state = State::default();
+        for (wallet, currency, amount) in &self.balances {
+            state.set_balance(wallet, currency, amount.clone());
+        }
+        let shared = Arc::new(Shared {
+            api_key: self.api_key,
+            secret_key: self.secret_key,
+            webhook_url: self.webhook_url,
+            host: format!("http://{addr}"),
+            signer: KeyPair::bundled()?.signer()?,
+            state: Mutex::new(state),
+            sent: Mutex::new(vec![]),
+            http: reqwest::Client::new(),
+        });
+
+        let mut router = Router::new();
+        for api in ENDPOINTS {
+            router = router.route(
+                &String::from(api),
+                post(
+                    move |Extension(shared): Extension<Arc<Shared>>,
+                          headers: HeaderMap,
+                          body: Bytes| async move {
+                        shared.handle(api, &headers, &body)
+                    },
+                ),
+            );
+        }
+        let app = router.layer(Extension(shared.clone()));
+
+        let (shutdown, signal) = oneshot::channel::<()>();
+        let server = axum::Server::from_tcp(listener)
+            .map_err(|e| Error::Msg(format!("Could not start the simulator: {e}")))?
+            .serve(app.into_make_service())
+            .with_graceful_shutdown(async {
+                signal.await.ok();
+            });
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                log::error!("Simulator stopped unexpectedly: {e}");
+            }
+        });
+
+        Ok(SimulatorHandle {
+            addr,
+            shared,
+            shutdown: Some(shutdown),
+        })
+    }
+}
+
+/// A running simulator. Stops serving once dropped.
+pub struct SimulatorHandle {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl SimulatorHandle {
+    /// Host to pass to [`Client::new`](crate::client::Client::new).
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Certificate used to sign the webhook notifications.
+    pub fn certificate(&self) -> CertificateResult {
+        self.shared.signer.certificate().clone()
+    }
+
+    /// Every notification emitted so far, oldest first.
+    pub fn notifications(&self) -> Vec<SentNotification> {
+        self.shared.sent.lock().unwrap().clone()
+    }
+
+    /// Marks the order as paid by the user and sends the `PAY_SUCCESS` notification.
+    pub async fn pay_order(&self, prepay_id: &str) -> Result<()> {
+        self.act(|state| state.pay_order(prepay_id)).await
+    }
+
+    /// Lets the order expire unpaid and sends the `PAY_CLOSED` notification.
+    pub async fn expire_order(&self, prepay_id: &str) -> Result<()> {
+        self.act(|state| state.expire_order(prepay_id)).await
+    }
+
+    /// Finishes all the transfers of a payout batch and sends the `PAYOUT` notification.
+    pub async fn complete_payout(&self, request_id: &str, success: bool) -> Result<()> {
+        self.act(|state| state.complete_payout(request_id, success))
+            .await
+    }
+
+    async fn act(
+        &self,
+        action: impl FnOnce(&mut State) -> std::result::Result<Reply, ApiError>,
+    ) -> Result<()> {
+        let reply =
+            action(&mut self.shared.state.lock().unwrap()).map_err(|e| Error::BinanceError {
+                response: BinanceContentError {
+                    status: "FAIL".into(),
+                    code: e.code,
+                    error_message: e.message,
+                },
+            })?;
+        for notification in self.shared.record(reply.notifications)? {
+            self.shared.deliver(&notification).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SimulatorHandle {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+struct Shared {
+    api_key: String,
+    secret_key: String,
+    webhook_url: Option<String>,
+    host: String,
+    signer: NotificationSigner,
+    state: Mutex<State>,
+    sent: Mutex<Vec<SentNotification>>,
+    http: reqwest::Client,
+}
+
+impl Shared {
+    fn handle(self: Arc<Self>, api: API, headers: &HeaderMap, body: &[u8]) -> Response {
+        let reply = self.authenticate(headers, body).and_then(|body| {
+            let request = if body.is_empty() {
+                Value::Null
+            } else {
+                serde_json::from_str(body).map_err(|e| {
+                    ApiError::new(
+                        StatusCode::BAD_REQUEST,
+                        ErrorCode::InvalidRequest,
+                        e.to_string(),
+                    )
+                })?
+            };
+            self.dispatch(api, &request)
+        });
+        match reply {
+            Ok(reply) => {
+                match self.record(reply.notifications) {
+                    Ok(notifications) => {
+                        let shared = self.clone();
+                        tokio::spawn(async move {
+                            for notification in notifications {
+                                if let Err(e) = shared.deliver(&notification).await {
+                                    log::warn!("Could not deliver the notification: {e}");
+                                }
+                            }
+                        });
+                    }
+                    Err(e) => log::warn!("Could not sign the notification: {e}"),
+                }
+                Json(json!({
+                    "status": "SUCCESS",
+                    "code": "000000",
+                    "data": reply.data,
+                    "errorMessage": "",
+                }))
+                .into_response()
+            }
+            Err(e) => (
+                e.status,
+                Json(json!({
+                    "status": "FAIL",
+                    "code": e.code.to_string(),
+                    "errorMessage": e.message,
+                })),
+            )
+                .into_response(),
+        }
+    }
+
+    /// Checks the `BinancePay-*` headers the same way Binance does and returns the body.
+    fn authenticate<'a>(
+        &self,
+        headers: &HeaderMap,
+        body: &'a [u8],
+    ) -> std::result::Result<&'a str, ApiError> {
+        let header = |key: &str| {
+            headers
+                .get(key)
+                .and_then(|val| val.to_str().ok())
+                .ok_or_else(|| {
+                    ApiError::new(
+                        StatusCode::BAD_REQUEST,
+                        ErrorCode::InvalidRequest,
+                        format!("Missing header {key}"),
+                    )
+                })
+        };
+        if header("BinancePay-Certificate-SN")? != self.api_key {
+            return Err(ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                ErrorCode::InvalidApiKeyOrIp,
+                "Invalid API key",
+            ));
+        }
+        let timestamp = header("BinancePay-Timestamp")?
+            .parse::<u128>()
+            .map_err(|_| {
+                ApiError::new(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::InvalidRequest,
+                    "Malformed timestamp",
+                )
+            })?;
+        let body = std::str::from_utf8(body).map_err(|e| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidRequest,
+                e.to_string(),
+            )
+        })?;
+        let content = RequestContent::new(
+            timestamp,
+            header("BinancePay-Nonce")?.to_string(),
+            Some(body.to_string()),
+        );
+        if content.sign(&self.secret_key) != header("BinancePay-Signature")? {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidSignature,
+                "Signature for this request is not valid.",
+            ));
+        }
+        Ok(body)
+    }
+
+    fn dispatch(&self, api: API, request: &Value) -> std::result::Result<Reply, ApiError> {
+        if api == API::QueryCertificate {
+            let certificate = self.signer.certificate();
+            return Ok(json!([{
+                "certSerial": certificate.cert_serial,
+                "certPublic": certificate.cert_public,
+            }])
+            .into());
+        }
+        let mut state = self.state.lock().unwrap();
+        match api {
+            API::CreateOrder => state.create_order(request, &self.host),
+            API::QueryOrder => state.query_order(request),
+            API::CloseOrder => state.close_order(request),
+            API::RefundOrder => state.refund_order(request),
+            API::QueryRefund => state.query_refund(request),
+            API::BalanceQuery => state.query_balance(request),
+            API::TransferFund => state.transfer_fund(request),
+            API::QueryTransfer => state.query_transfer(request),
+            API::BatchPayout => state.batch_payout(request),
+            API::PayoutQuery => state.query_payout(request),
+            API::CreateSubMerchant => state.create_sub_merchant(request),
+            API::QueryCertificate => unreachable!(),
+        }
+    }
+
+    /// Signs the notification bodies and keeps them for later inspection.
+    fn record(&self, bodies: Vec<String>) -> Result<Vec<SentNotification>> {
+        let notifications = bodies
+            .into_iter()
+            .map(|body| self.signer.sign(body))
+            .collect::<Result<Vec<_>>>()?;
+        self.sent
+            .lock()
+            .unwrap()
+            .extend(notifications.iter().cloned());
+        Ok(notifications)
+    }
+
+    async fn deliver(&self, notification: &SentNotification) -> Result<()> {
+        match &self.webhook_url {
+            Some(url) => webhook::deliver(&self.http, url, notification).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::order::close::{Request as CloseOrderRequest, Response as CloseOrderResponse};
+    use crate::api::order::create::{
+        Goods, GoodsCategory, GoodsType, Request as OrderRequest, TerminalType,
+    };
+    use crate::api::order::query::{Request as QueryOrderRequest, Status};
+    use crate::api::payout::initiate::{
+        ReceiveType, Request as PayoutRequest, TransferDetailReq, TransferMethod,
+    };
+    use crate::api::payout::query::{BatchStatus, Request as PayoutQueryRequest};
+    use crate::api::refund::initiate::Request as RefundRequest;
+    use crate::api::refund::query::{RefundStatus, Request as RefundQueryRequest};
+    use crate::api::transfer::initiate::{Request as TransferRequest, TransferType};
+    use crate::api::wallet_balance::query::{Request as BalanceRequest, WalletType};
+    use crate::c2b::currency::Currency;
+    use crate::c2b::webhook::notification::{order, refund, Notification};
+    use crate::c2b::webhook::verification::{Verifier, Verify};
+    use crate::client::Client;
+    use crate::errors::ErrorCategory;
+
+    fn client(simulator: &SimulatorHandle) -> Client {
+        Client::new(
+            Some("api_key".into()),
+            Some("api_secret".into()),
+            simulator.url(),
+        )
+    }
+
+    fn order() -> OrderRequest {
+        OrderRequest::builder()
+            .terminal_type(TerminalType::Web)
+            .merchant_trade_no(crate::utils::create_nonce(10))
+            .order_amount(Amount::from(10u32))
+            .currency(Currency::USDT)
+            .goods(Goods::new(
+                GoodsType::VirtualGoods,
+                GoodsCategory::Electronics,
+                "sku",
+                "Laptop",
+            ))
+            .build()
+            .unwrap()
+    }
+
+    fn verified(verifier: &Verifier, notification: &SentNotification) -> Notification {
+        verifier
+            .verify(&notification.header_map().unwrap(), &notification.body)
+            .unwrap();
+        Notification::try_from(notification.body.as_str()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_order_pay_refund_lifecycle() {
+        let simulator = Simulator::new("api_key", "api_secret")
+            .start()
+            .await
+            .unwrap();
+        let client = client(&simulator);
+        let verifier = Verifier::from_api(&client).await.unwrap();
+
+        let order = order().create(&client).await.unwrap();
+        let query = QueryOrderRequest::new(Some(order.prepay_id.clone()), None);
+        assert!(matches!(
+            query.query(&client).await.unwrap().status,
+            Status::Initial
+        ));
+
+        simulator.pay_order(&order.prepay_id).await.unwrap();
+        let paid = query.query(&client).await.unwrap();
+        assert!(matches!(paid.status, Status::Paid));
+        assert!(paid.transaction_id.is_some());
+        match verified(&verifier, &simulator.notifications()[0]) {
+            Notification::Order { biz_status, .. } => {
+                assert_eq!(biz_status, order::BizStatus::PaySuccess)
+            }
+            _ => panic!("Unexpected notification type"),
+        }
+
+        let refund = RefundRequest {
+            refund_request_id: "refund1".into(),
+            prepay_id: order.prepay_id.clone(),
+            refund_amount: Amount::from(10u32),
+            refund_reason: None,
+        };
+        refund.initiate(&client).await.unwrap();
+        let refund_query = RefundQueryRequest {
+            refund_request_id: "refund1".into(),
+        };
+        assert!(matches!(
+            refund_query.query(&client).await.unwrap().refund_status,
+            RefundStatus::RefundSuccess
+        ));
+        assert!(matches!(
+            query.query(&client).await.unwrap().status,
+            Status::Refunded
+        ));
+        match verified(&verifier, &simulator.notifications()[1]) {
+            Notification::Refund {
+                biz_status,
+                refund_detail,
+                ..
+            } => {
+                assert_eq!(biz_status, refund::BizStatus::RefundSuccess);
+                assert_eq!(
+                    refund_detail.refund_info.refunded_amount,
+                    Amount::from(10u32)
+                );
+            }
+            _ => panic!("Unexpected notification type"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_close_order() {
+        let simulator = Simulator::new("api_key", "api_secret")
+            .start()
+            .await
+            .unwrap();
+        let client = client(&simulator);
+        let order = order().create(&client).await.unwrap();
+        let close = CloseOrderRequest::new(Some(order.prepay_id.clone()), None);
+        assert_eq!(
+            close.close(&client).await.unwrap(),
+            CloseOrderResponse::Success
+        );
+        assert_eq!(
+            close.close(&client).await.unwrap(),
+            CloseOrderResponse::Failure
+        );
+        let error = simulator.pay_order(&order.prepay_id).await.unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::InvalidOrderStatus));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_signature() {
+        let simulator = Simulator::new("api_key", "api_secret")
+            .start()
+            .await
+            .unwrap();
+        let client = Client::new(
+            Some("api_key".into()),
+            Some("wrong_secret".into()),
+            simulator.url(),
+        );
+        let error = order().create(&client).await.unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::InvalidSignature));
+        let client = Client::new(
+            Some("wrong_key".into()),
+            Some("api_secret".into()),
+            simulator.url(),
+        );
+        assert!(matches!(
+            order().create(&client).await,
+            Err(Error::Unauthorized)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_and_payout() {
+        let simulator = Simulator::new("api_key", "api_secret")
+            .balance("FUNDING_WALLET", "USDT", Amount::from(100u32))
+            .start()
+            .await
+            .unwrap();
+        let client = client(&simulator);
+
+        TransferRequest {
+            request_id: "transfer1".into(),
+            currency: Currency::USDT,
+            amount: Amount::from(40u32),
+            transfer_type: TransferType::ToMain,
+        }
+        .initiate(&client)
+        .await
+        .unwrap();
+        let spot = BalanceRequest {
+            wallet: WalletType::SpotWallet,
+            currency: Currency::USDT,
+        };
+        assert_eq!(
+            spot.query(&client).await.unwrap().balance,
+            Amount::from(40u32)
+        );
+
+        let payout = PayoutRequest {
+            request_id: "payout1".into(),
+            biz_scene: None,
+            batch_name: "batch".into(),
+            currency: Currency::USDT,
+            total_amount: Amount::from(30u32),
+            total_number: 1,
+            transfer_detail_list: vec![TransferDetailReq {
+                merchant_send_id: "send1".into(),
+                receive_type: ReceiveType::PayId,
+                receiver: "354205155".into(),
+                transfer_amount: Amount::from(30u32),
+                transfer_method: TransferMethod::SpotWallet,
+                remark: None,
+            }],
+        };
+        payout.initiate(&client).await.unwrap();
+        let error = payout.initiate(&client).await.unwrap_err();
+        assert_eq!(error.category(), Some(ErrorCategory::Duplicate));
+        assert_eq!(
+            spot.query(&client).await.unwrap().balance,
+            Amount::from(10u32)
+        );
+
+        simulator.complete_payout("payout1", true).await.unwrap();
+        let result = PayoutQueryRequest::new("payout1".into(), None)
+            .query(&client)
+            .await
+            .unwrap();
+        assert_eq!(result.batch_status, BatchStatus::Success);
+        assert!(matches!(
+            Notification::try_from(simulator.notifications()[0].body.as_str()).unwrap(),
+            Notification::Payout { .. }
+        ));
+    }
+}
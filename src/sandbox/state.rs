@@ -0,0 +1,709 @@
+//! In-memory bookkeeping of the simulator: orders, refunds, transfers, payouts and balances.
+
+use crate::c2b::amount::Amount;
+use crate::c2b::currency::Currency;
+use crate::errors::ErrorCode;
+use crate::utils;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Merchant id reported by every simulated response.
+pub(crate) const MERCHANT_ID: u64 = 100100006288;
+
+/// Number of refunds allowed for a single order.
+const REFUND_ATTEMPTS: u8 = 10;
+
+/// Business error returned to the client inside a `FAIL` envelope.
+#[derive(Debug)]
+pub(crate) struct ApiError {
+    pub status: StatusCode,
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub(crate) fn new(status: StatusCode, code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn invalid(message: impl Into<String>) -> Self {
+        Self::new(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidParamWrongValue,
+            message,
+        )
+    }
+}
+
+/// Result of a simulated call: the envelope's `data` and the webhooks it triggers.
+pub(crate) struct Reply {
+    pub data: Value,
+    pub notifications: Vec<String>,
+}
+
+impl From<Value> for Reply {
+    fn from(data: Value) -> Self {
+        Self {
+            data,
+            notifications: vec![],
+        }
+    }
+}
+
+type Outcome = Result<Reply, ApiError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OrderStatus {
+    Initial,
+    Paid,
+    Canceled,
+    Refunding,
+    Refunded,
+    Expired,
+}
+
+impl OrderStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Initial => "INITIAL",
+            OrderStatus::Paid => "PAID",
+            OrderStatus::Canceled => "CANCELED",
+            OrderStatus::Refunding => "REFUNDING",
+            OrderStatus::Refunded => "REFUNDED",
+            OrderStatus::Expired => "EXPIRED",
+        }
+    }
+}
+
+struct Order {
+    prepay_id: String,
+    merchant_trade_no: String,
+    terminal_type: String,
+    amount: Amount,
+    currency: String,
+    goods_name: String,
+    goods_detail: String,
+    status: OrderStatus,
+    create_time: u64,
+    transact_time: u64,
+    transaction_id: Option<String>,
+    open_user_id: String,
+    refunded: Amount,
+    refund_attempts: u8,
+}
+
+struct Refund {
+    response: Value,
+}
+
+struct Transfer {
+    status: &'static str,
+}
+
+struct Payout {
+    status: &'static str,
+    currency: String,
+    total_amount: Amount,
+    details: Vec<Value>,
+}
+
+/// Everything the simulator remembers between calls.
+#[derive(Default)]
+pub(crate) struct State {
+    orders: HashMap<String, Order>,
+    trade_nos: HashMap<String, String>,
+    refunds: HashMap<String, Refund>,
+    transfers: HashMap<String, Transfer>,
+    payouts: HashMap<String, Payout>,
+    balances: HashMap<(String, String), Amount>,
+    last_id: u64,
+}
+
+impl State {
+    pub(crate) fn set_balance(&mut self, wallet: &str, currency: &str, amount: Amount) {
+        self.balances
+            .insert((wallet.to_string(), currency.to_string()), amount);
+    }
+
+    fn balance(&self, wallet: &str, currency: &str) -> Amount {
+        self.balances
+            .get(&(wallet.to_string(), currency.to_string()))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn credit(&mut self, wallet: &str, currency: &str, amount: Amount) {
+        let balance = self.balance(wallet, currency) + amount;
+        self.set_balance(wallet, currency, balance);
+    }
+
+    fn debit(&mut self, wallet: &str, currency: &str, amount: Amount) -> Result<(), ApiError> {
+        let balance = self.balance(wallet, currency);
+        if balance < amount {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InsufficientBalance,
+                format!("Insufficient {currency} balance in {wallet}"),
+            ));
+        }
+        self.set_balance(wallet, currency, balance - amount);
+        Ok(())
+    }
+
+    fn next_id(&mut self) -> String {
+        if self.last_id == 0 {
+            self.last_id = utils::get_current_timestamp() as u64 * 10_000;
+        }
+        self.last_id += 1;
+        self.last_id.to_string()
+    }
+
+    fn find_order(
+        &mut self,
+        prepay_id: Option<&str>,
+        trade_no: Option<&str>,
+    ) -> Result<&mut Order, ApiError> {
+        let prepay_id = match (prepay_id, trade_no) {
+            (Some(prepay_id), _) => Some(prepay_id.to_string()),
+            (None, Some(trade_no)) => self.trade_nos.get(trade_no).cloned(),
+            (None, None) => {
+                return Err(ApiError::invalid(
+                    "Either prepayId or merchantTradeNo must be present",
+                ))
+            }
+        };
+        prepay_id
+            .and_then(|id| self.orders.get_mut(&id))
+            .ok_or_else(|| {
+                ApiError::new(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::OrderNotFound,
+                    "Order not found",
+                )
+            })
+    }
+
+    pub(crate) fn create_order(&mut self, request: &Value, host: &str) -> Outcome {
+        let merchant_trade_no = required_str(request, "merchantTradeNo")?;
+        if self.trade_nos.contains_key(merchant_trade_no) {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::DuplicateRequest,
+                "Duplicate merchantTradeNo",
+            ));
+        }
+        let amount = required_amount(request, "orderAmount")?;
+        let goods = request
+            .get("goods")
+            .ok_or_else(|| ApiError::invalid("goods is required"))?;
+        let terminal_type = request
+            .pointer("/env/terminalType")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ApiError::invalid("env.terminalType is required"))?;
+        let prepay_id = self.next_id();
+        let now = utils::get_current_timestamp() as u64;
+        let order = Order {
+            prepay_id: prepay_id.clone(),
+            merchant_trade_no: merchant_trade_no.to_string(),
+            terminal_type: terminal_type.to_string(),
+            amount,
+            currency: required_currency(request)?.to_string(),
+            goods_name: required_str(goods, "goodsName")?.to_string(),
+            goods_detail: optional_str(goods, "goodsDetail").unwrap_or("").to_string(),
+            status: OrderStatus::Initial,
+            create_time: now,
+            transact_time: 0,
+            transaction_id: None,
+            open_user_id: String::new(),
+            refunded: Amount::zero(),
+            refund_attempts: REFUND_ATTEMPTS,
+        };
+        self.trade_nos
+            .insert(order.merchant_trade_no.clone(), prepay_id.clone());
+        self.orders.insert(prepay_id.clone(), order);
+        Ok(json!({
+            "prepayId": prepay_id,
+            "terminalType": terminal_type,
+            "expireTime": now + 3_600_000,
+            "qrcodeLink": format!("{host}/qr/{prepay_id}.jpg"),
+            "qrContent": format!("{host}/qr/{prepay_id}"),
+            "checkoutUrl": format!("{host}/checkout/{prepay_id}"),
+            "deeplink": format!("bnc://app.binance.com/payment/secpay/{prepay_id}"),
+            "universalUrl": format!("{host}/payment/secpay?prepayId={prepay_id}"),
+        })
+        .into())
+    }
+
+    pub(crate) fn query_order(&mut self, request: &Value) -> Outcome {
+        let order = self.find_order(
+            optional_str(request, "prepayId"),
+            optional_str(request, "merchantTradeNo"),
+        )?;
+        Ok(json!({
+            "merchantId": MERCHANT_ID,
+            "prepayId": order.prepay_id,
+            "transactionId": order.transaction_id,
+            "merchantTradeNo": order.merchant_trade_no,
+            "tradeType": order.terminal_type,
+            "status": order.status.as_str(),
+            "currency": order.currency,
+            "totalFee": order.amount,
+            "productName": order.goods_name,
+            "productDetail": order.goods_detail,
+            "openUserId": order.open_user_id,
+            "transactTime": order.transact_time,
+            "createTime": order.create_time,
+        })
+        .into())
+    }
+
+    pub(crate) fn close_order(&mut self, request: &Value) -> Outcome {
+        let order = self.find_order(
+            optional_str(request, "prepayId"),
+            optional_str(request, "merchantTradeNo"),
+        )?;
+        if order.status != OrderStatus::Initial {
+            return Ok(json!(false).into());
+        }
+        order.status = OrderStatus::Canceled;
+        let notification = order_notification(order, "PAY", "PAY_CLOSED", json!({}));
+        Ok(Reply {
+            data: json!(true),
+            notifications: vec![notification],
+        })
+    }
+
+    /// Simulates the payer completing the checkout.
+    pub(crate) fn pay_order(&mut self, prepay_id: &str) -> Outcome {
+        let transaction_id = format!("M_P_{}", self.next_id());
+        let order = self.find_order(Some(prepay_id), None)?;
+        if order.status != OrderStatus::Initial {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidOrderStatus,
+                format!("Order can not be paid in status {}", order.status.as_str()),
+            ));
+        }
+        order.status = OrderStatus::Paid;
+        order.transact_time = utils::get_current_timestamp() as u64;
+        order.transaction_id = Some(transaction_id);
+        order.open_user_id = utils::create_nonce(32);
+        let notification = order_notification(order, "PAY", "PAY_SUCCESS", json!({}));
+        let (currency, amount) = (order.currency.clone(), order.amount.clone());
+        self.credit("FUNDING_WALLET", &currency, amount);
+        Ok(Reply {
+            data: json!(true),
+            notifications: vec![notification],
+        })
+    }
+
+    /// Simulates the checkout timing out before the payer paid.
+    pub(crate) fn expire_order(&mut self, prepay_id: &str) -> Outcome {
+        let order = self.find_order(Some(prepay_id), None)?;
+        if order.status != OrderStatus::Initial {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidOrderStatus,
+                format!("Order can not expire in status {}", order.status.as_str()),
+            ));
+        }
+        order.status = OrderStatus::Expired;
+        let notification = order_notification(order, "PAY", "PAY_CLOSED", json!({}));
+        Ok(Reply {
+            data: json!(true),
+            notifications: vec![notification],
+        })
+    }
+
+    pub(crate) fn refund_order(&mut self, request: &Value) -> Outcome {
+        let refund_request_id = required_str(request, "refundRequestId")?.to_string();
+        if let Some(refund) = self.refunds.get(&refund_request_id) {
+            let mut response = refund.response.clone();
+            response["duplicateRequest"] = json!("Y");
+            response
+                .as_object_mut()
+                .map(|response| response.remove("refundStatus"));
+            return Ok(response.into());
+        }
+        let refund_amount = required_amount(request, "refundAmount")?;
+        let order = self.find_order(Some(required_str(request, "prepayId")?), None)?;
+        if !matches!(order.status, OrderStatus::Paid | OrderStatus::Refunding) {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidOrderStatus,
+                format!(
+                    "Order can not be refunded in status {}",
+                    order.status.as_str()
+                ),
+            ));
+        }
+        let remaining = order.amount.clone() - order.refunded.clone();
+        if refund_amount <= Amount::zero() || refund_amount > remaining {
+            return Err(ApiError::invalid(format!(
+                "Refund amount must be between 0 and {remaining}"
+            )));
+        }
+        // The last allowed attempt refunds everything that is left.
+        let refund_amount = if order.refund_attempts == 1 {
+            remaining
+        } else {
+            refund_amount
+        };
+        let (currency, prepay_id) = (order.currency.clone(), order.prepay_id.clone());
+        self.debit("FUNDING_WALLET", &currency, refund_amount.clone())?;
+
+        let order = self.find_order(Some(&prepay_id), None)?;
+        order.refunded = order.refunded.clone() + refund_amount.clone();
+        order.refund_attempts -= 1;
+        order.status = if order.refunded == order.amount {
+            OrderStatus::Refunded
+        } else {
+            OrderStatus::Refunding
+        };
+        let response = json!({
+            "refundRequestId": refund_request_id,
+            "prepayId": order.prepay_id,
+            "orderAmount": order.amount.to_fixed(),
+            "refundedAmount": order.refunded.to_fixed(),
+            "refundAmount": refund_amount.to_fixed(),
+            "remainingAttempts": order.refund_attempts,
+            "payerOpenId": order.open_user_id,
+            "duplicateRequest": "N",
+        });
+        let notification = order_notification(
+            order,
+            "PAY_REFUND",
+            "REFUND_SUCCESS",
+            json!({ "refundInfo": response, "commission": 0 }),
+        );
+        let mut stored = response.clone();
+        stored["refundStatus"] = json!("REFUND_SUCCESS");
+        self.refunds
+            .insert(refund_request_id, Refund { response: stored });
+        Ok(Reply {
+            data: response,
+            notifications: vec![notification],
+        })
+    }
+
+    pub(crate) fn query_refund(&mut self, request: &Value) -> Outcome {
+        let refund_request_id = required_str(request, "refundRequestId")?;
+        let refund = self.refunds.get(refund_request_id).ok_or_else(|| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::OrderNotFound,
+                "Refund not found",
+            )
+        })?;
+        let mut response = refund.response.clone();
+        response
+            .as_object_mut()
+            .map(|response| response.remove("duplicateRequest"));
+        Ok(response.into())
+    }
+
+    pub(crate) fn query_balance(&mut self, request: &Value) -> Outcome {
+        let wallet = required_str(request, "wallet")?;
+        let currency = required_currency(request)?;
+        let balance = self.balance(wallet, currency);
+        Ok(json!({
+            "balance": balance,
+            "asset": currency,
+            "fiat": "USD",
+            "availableFiatValuation": balance,
+            "availableBtcValuation": Amount::zero(),
+        })
+        .into())
+    }
+
+    pub(crate) fn transfer_fund(&mut self, request: &Value) -> Outcome {
+        let request_id = required_str(request, "requestId")?.to_string();
+        if self.transfers.contains_key(&request_id) {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::DuplicateRequest,
+                "Duplicate requestId",
+            ));
+        }
+        let currency = required_currency(request)?.to_string();
+        let amount = required_amount(request, "amount")?;
+        let transfer_type = required_str(request, "transferType")?.to_string();
+        let (from, to) = match transfer_type.as_str() {
+            "TO_MAIN" => ("FUNDING_WALLET", "SPOT_WALLET"),
+            "TO_PAY" => ("SPOT_WALLET", "FUNDING_WALLET"),
+            other => return Err(ApiError::invalid(format!("Unknown transferType {other}"))),
+        };
+        self.debit(from, &currency, amount.clone())?;
+        self.credit(to, &currency, amount);
+        self.transfers
+            .insert(request_id.clone(), Transfer { status: "SUCCESS" });
+        Ok(json!({
+            "tranId": request_id,
+            "status": "SUCCESS",
+            "currency": currency,
+            "amount": required_str(request, "amount")?,
+            "transferType": transfer_type,
+        })
+        .into())
+    }
+
+    pub(crate) fn query_transfer(&mut self, request: &Value) -> Outcome {
+        let tran_id = required_str(request, "tran_id")?;
+        let transfer = self.transfers.get(tran_id).ok_or_else(|| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::OrderNotFound,
+                "Transfer not found",
+            )
+        })?;
+        Ok(json!({ "tran_id": tran_id, "status": transfer.status }).into())
+    }
+
+    pub(crate) fn batch_payout(&mut self, request: &Value) -> Outcome {
+        let request_id = required_str(request, "requestId")?.to_string();
+        if self.payouts.contains_key(&request_id) {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::DuplicateRequest,
+                "Duplicate requestId",
+            ));
+        }
+        let currency = required_currency(request)?.to_string();
+        let total_amount = required_amount(request, "totalAmount")?;
+        let total_number = request
+            .get("totalNumber")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| ApiError::invalid("totalNumber is required"))?;
+        let requested = request
+            .get("transferDetailList")
+            .and_then(Value::as_array)
+            .ok_or_else(|| ApiError::invalid("transferDetailList is required"))?;
+        if requested.len() as u64 != total_number {
+            return Err(ApiError::invalid(
+                "totalNumber must be equal to the detail transfer count",
+            ));
+        }
+        let mut debits: Vec<(String, Amount)> = vec![];
+        for detail in requested {
+            let amount = required_amount(detail, "transferAmount")?;
+            debits.push((required_str(detail, "transferMethod")?.to_string(), amount));
+        }
+        if debits.iter().map(|(_, amount)| amount).sum::<Amount>() != total_amount {
+            return Err(ApiError::invalid(
+                "totalAmount must be equal to the sum of all the detail transfers",
+            ));
+        }
+        for (wallet, amount) in &debits {
+            self.debit(wallet, &currency, amount.clone())?;
+        }
+        let mut details = vec![];
+        for detail in requested {
+            let receiver = required_str(detail, "receiver")?;
+            details.push(json!({
+                "orderId": self.next_id().parse::<u64>().unwrap_or_default(),
+                "merchantSendId": required_str(detail, "merchantSendId")?,
+                "payerId": MERCHANT_ID,
+                "amount": required_amount(detail, "transferAmount")?.to_fixed(),
+                "receiveType": required_str(detail, "receiveType")?,
+                "receiver": receiver,
+                "payeeId": receiver.parse::<u64>().unwrap_or_default(),
+                "transferMethod": required_str(detail, "transferMethod")?,
+                "status": "PROCESSING",
+                "remark": detail.get("remark").cloned().unwrap_or(Value::Null),
+            }));
+        }
+        self.payouts.insert(
+            request_id.clone(),
+            Payout {
+                status: "ACCEPTED",
+                currency,
+                total_amount,
+                details,
+            },
+        );
+        Ok(json!({ "requestId": request_id, "status": "ACCEPTED" }).into())
+    }
+
+    /// Simulates Binance finishing every transfer of the batch.
+    pub(crate) fn complete_payout(&mut self, request_id: &str, success: bool) -> Outcome {
+        let payout = self.payouts.get_mut(request_id).ok_or_else(|| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::OrderNotFound,
+                "Payout not found",
+            )
+        })?;
+        if !matches!(payout.status, "ACCEPTED" | "PROCESSING") {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidOrderStatus,
+                format!("Payout already finished with {}", payout.status),
+            ));
+        }
+        let (batch_status, detail_status) = if success {
+            ("SUCCESS", "SUCCESS")
+        } else {
+            ("FAILED", "FAIL")
+        };
+        payout.status = batch_status;
+        for detail in payout.details.iter_mut() {
+            detail["status"] = json!(detail_status);
+        }
+        let (currency, total_amount, total_number) = (
+            payout.currency.clone(),
+            payout.total_amount.clone(),
+            payout.details.len(),
+        );
+        if !success {
+            let refunds: Vec<(String, Amount)> = payout
+                .details
+                .iter()
+                .map(|detail| {
+                    (
+                        detail["transferMethod"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        detail["amount"]
+                            .as_str()
+                            .and_then(|amount| amount.parse().ok())
+                            .unwrap_or_default(),
+                    )
+                })
+                .collect();
+            for (wallet, amount) in refunds {
+                self.credit(&wallet, &currency, amount);
+            }
+        }
+        let data = json!({
+            "batchStatus": batch_status,
+            "currency": currency,
+            "merchantId": MERCHANT_ID,
+            "requestId": request_id,
+            "totalAmount": total_amount,
+            "totalNumber": total_number,
+        });
+        Ok(Reply {
+            data: json!(true),
+            notifications: vec![notification_body(
+                "PAYOUT",
+                &self.next_id(),
+                batch_status,
+                &data,
+            )],
+        })
+    }
+
+    pub(crate) fn query_payout(&mut self, request: &Value) -> Outcome {
+        let request_id = required_str(request, "requestId")?;
+        let payout = self.payouts.get(request_id).ok_or_else(|| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::OrderNotFound,
+                "Payout not found",
+            )
+        })?;
+        let wanted = match optional_str(request, "detailStatus") {
+            None | Some("All") => None,
+            Some("Processing") => Some("PROCESSING"),
+            Some("Success") => Some("SUCCESS"),
+            Some("Fail") => Some("FAIL"),
+            Some(other) => return Err(ApiError::invalid(format!("Unknown detailStatus {other}"))),
+        };
+        let details: Vec<&Value> = payout
+            .details
+            .iter()
+            .filter(|detail| wanted.is_none_or(|status| detail["status"] == status))
+            .collect();
+        Ok(json!({
+            "requestId": request_id,
+            "batchStatus": payout.status,
+            "merchantId": MERCHANT_ID,
+            "currency": payout.currency,
+            "totalAmount": payout.total_amount,
+            "totalNumber": payout.details.len(),
+            "transferDetailList": details,
+        })
+        .into())
+    }
+
+    pub(crate) fn create_sub_merchant(&mut self, request: &Value) -> Outcome {
+        for field in ["merchantName", "merchantMcc", "country"] {
+            required_str(request, field)?;
+        }
+        if request
+            .get("merchantType")
+            .and_then(Value::as_u64)
+            .is_none()
+        {
+            return Err(ApiError::invalid("merchantType is required"));
+        }
+        let sub_merchant_id: u64 = self.next_id().parse().unwrap_or_default();
+        Ok(json!({ "subMerchantId": sub_merchant_id }).into())
+    }
+}
+
+fn order_notification(order: &Order, biz_type: &str, biz_status: &str, extra: Value) -> String {
+    let mut data = json!({
+        "merchantTradeNo": order.merchant_trade_no,
+        "productType": "Others",
+        "productName": order.goods_name,
+        "tradeType": order.terminal_type,
+        "totalFee": order.amount,
+        "currency": order.currency,
+        "openUserId": order.open_user_id,
+        "transactTime": order.transact_time,
+        "transactionId": order.transaction_id,
+    });
+    if let (Some(data), Value::Object(extra)) = (data.as_object_mut(), extra) {
+        data.extend(extra);
+    }
+    notification_body(biz_type, &order.prepay_id, biz_status, &data)
+}
+
+/// `bizId` is sent as a bare number which may exceed `u64`, hence the manual formatting.
+fn notification_body(biz_type: &str, biz_id: &str, biz_status: &str, data: &Value) -> String {
+    format!(
+        r#"{{"bizType":{},"data":{},"bizId":{},"bizStatus":{}}}"#,
+        json!(biz_type),
+        json!(data.to_string()),
+        biz_id,
+        json!(biz_status)
+    )
+}
+
+fn optional_str<'a>(request: &'a Value, field: &str) -> Option<&'a str> {
+    request.get(field).and_then(Value::as_str)
+}
+
+fn required_str<'a>(request: &'a Value, field: &str) -> Result<&'a str, ApiError> {
+    optional_str(request, field)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| ApiError::invalid(format!("{field} is required")))
+}
+
+fn required_currency(request: &Value) -> Result<&str, ApiError> {
+    let currency = required_str(request, "currency")?;
+    currency.parse::<Currency>().map_err(|_| {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::IllegalCurrency,
+            format!("Currency {currency} is not supported"),
+        )
+    })?;
+    Ok(currency)
+}
+
+/// Amounts are sent either as JSON numbers or as decimal strings depending on the endpoint.
+fn required_amount(request: &Value, field: &str) -> Result<Amount, ApiError> {
+    request
+        .get(field)
+        .and_then(|amount| Amount::deserialize(amount).ok())
+        .ok_or_else(|| ApiError::invalid(format!("{field} must be a decimal amount")))
+}
@@ -0,0 +1,27 @@
+//! Delivery of the simulator's webhook notifications.
+
+use super::SentNotification;
+use crate::errors::{Error, Result};
+
+/// Posts the signed notification to the merchant's webhook endpoint.
+pub(crate) async fn deliver(
+    client: &reqwest::Client,
+    url: &str,
+    notification: &SentNotification,
+) -> Result<()> {
+    let response = client
+        .post(url)
+        .headers(notification.header_map()?)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(notification.body.clone())
+        .send()
+        .await?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(Error::Msg(format!(
+            "Webhook endpoint responded with {}",
+            response.status()
+        )))
+    }
+}
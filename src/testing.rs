@@ -0,0 +1,331 @@
+/*!
+Signed webhook notifications for testing the handlers of downstream crates.
+
+[`KeyPair`] stands in for the key Binance signs its notifications with, [`NotificationSigner`]
+serializes a [`Notification`] into the body Binance sends, attaches the `BinancePay-*` headers
+and its
+[`verifier`](NotificationSigner::verifier) accepts them, like the one built from the
+certificate API does for the real notifications.
+
+```
+# use bpay::c2b::webhook::notification::{batch_payout, Notification};
+# use bpay::c2b::webhook::verification::Verify;
+# use bpay::testing::KeyPair;
+# fn main() -> bpay::errors::Result<()> {
+# let payout_detail: batch_payout::Payout = serde_json::from_str(
+#     r#"{"batchStatus":"SUCCESS","currency":"BUSD","merchantId":100100006288,"requestId":"gg8127129","totalAmount":2,"totalNumber":2}"#,
+# )?;
+let signer = KeyPair::bundled()?.signer()?;
+let signed = signer.sign_notification(&Notification::Payout {
+    biz_id: 29383937493038367292,
+    biz_status: batch_payout::BizStatus::Success,
+    payout_detail,
+})?;
+
+signer.verifier()?.verify(&signed.headers, &signed.body)?;
+let notification = Notification::try_from(signed.body.as_str())?;
+assert!(matches!(notification, Notification::Payout { .. }));
+# Ok(())
+# }
+```
+*/
+
+use crate::c2b::webhook::certificate::CertificateResult;
+use crate::c2b::webhook::notification::Notification;
+use crate::c2b::webhook::verification::Verifier;
+use crate::errors::{Error, Result};
+use crate::utils;
+use ring::{digest, rand, signature};
+use rsa::pkcs1::EncodeRsaPrivateKey;
+use rsa::pkcs8::{EncodePublicKey, LineEnding};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Key pair shipped with the crate, the same one the verification tests use.
+const BUNDLED: &str = include_str!("../data/key_pair.json");
+
+/// RSA key pair with the certificate Binance would serve for its public key.
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeyPair {
+    /// PKCS#1 private key in PEM format.
+    private_key: String,
+    cert_public: String,
+    #[serde(rename = "cert_public_md5_hash")]
+    cert_serial: String,
+}
+
+impl KeyPair {
+    /// Generates a 2048 bits key pair, which takes a few seconds in debug builds.
+    pub fn generate() -> Result<Self> {
+        let private_key = rsa::RsaPrivateKey::new(&mut ::rand::thread_rng(), 2048)
+            .map_err(|e| Error::Msg(format!("Could not generate the key pair: {e}")))?;
+        let cert_public = private_key
+            .to_public_key()
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|e| Error::Msg(format!("Could not encode the public key: {e}")))?;
+        // Binance's serial is an opaque MD5 hash, any fingerprint of the key will do.
+        let fingerprint = digest::digest(&digest::SHA256, cert_public.as_bytes());
+        Ok(Self {
+            private_key: private_key
+                .to_pkcs1_pem(LineEnding::LF)
+                .map_err(|e| Error::Msg(format!("Could not encode the private key: {e}")))?
+                .to_string(),
+            cert_serial: hex::encode_upper(&fingerprint.as_ref()[..16]),
+            cert_public,
+        })
+    }
+
+    /// Key pair shipped with the crate, instantly available but public.
+    pub fn bundled() -> Result<Self> {
+        Ok(serde_json::from_str(BUNDLED)?)
+    }
+
+    pub fn private_key(&self) -> &str {
+        &self.private_key
+    }
+
+    /// Certificate as returned by the certificate API.
+    pub fn certificate(&self) -> CertificateResult {
+        CertificateResult {
+            cert_serial: self.cert_serial.clone(),
+            cert_public: self.cert_public.clone(),
+        }
+    }
+
+    pub fn signer(&self) -> Result<NotificationSigner> {
+        NotificationSigner::new(&self.private_key, self.certificate())
+    }
+}
+
+/// A notification body with the headers Binance signs it with.
+#[derive(Debug, Clone)]
+pub struct SignedNotification {
+    /// `BinancePay-*` headers attached to the request.
+    pub headers: Vec<(String, String)>,
+
+    /// Raw JSON body of the request.
+    pub body: String,
+}
+
+impl SignedNotification {
+    /// Returns the headers as a [`reqwest::header::HeaderMap`] ready to be passed to the verifier.
+    pub fn header_map(&self) -> Result<reqwest::header::HeaderMap> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (key, val) in &self.headers {
+            headers.insert(
+                reqwest::header::HeaderName::from_bytes(key.as_bytes())
+                    .map_err(|e| Error::Msg(e.to_string()))?,
+                reqwest::header::HeaderValue::from_str(val)?,
+            );
+        }
+        Ok(headers)
+    }
+}
+
+/// Signs notification bodies the way Binance Pay does, RSA SHA256 over
+/// `timestamp\nnonce\nbody\n`.
+pub struct NotificationSigner {
+    key_pair: signature::RsaKeyPair,
+    certificate: CertificateResult,
+}
+
+impl NotificationSigner {
+    /// `private_key` is a PKCS#1 PEM matching the public key of the certificate.
+    pub fn new(private_key: &str, certificate: CertificateResult) -> Result<Self> {
+        let der = base64::decode(strip_pem(private_key))?;
+        Ok(Self {
+            key_pair: signature::RsaKeyPair::from_der(&der)
+                .map_err(|e| Error::Msg(format!("Invalid webhook private key: {e}")))?,
+            certificate,
+        })
+    }
+
+    pub fn certificate(&self) -> &CertificateResult {
+        &self.certificate
+    }
+
+    /// Verifier accepting the notifications signed by this signer.
+    pub fn verifier(&self) -> Result<Verifier> {
+        Ok(Verifier::try_from(self.certificate.clone())?)
+    }
+
+    /// Signs the notification as Binance would send it, see [`notification_body`].
+    pub fn sign_notification(&self, notification: &Notification) -> Result<SignedNotification> {
+        self.sign(notification_body(notification)?)
+    }
+
+    /// Signs a raw body with the current timestamp and a random nonce.
+    pub fn sign(&self, body: impl Into<String>) -> Result<SignedNotification> {
+        let timestamp = utils::get_current_timestamp().to_string();
+        self.sign_with(timestamp, utils::create_nonce(32), body)
+    }
+
+    /// Signs the body with the given timestamp, in milliseconds, and nonce, e.g. to test the
+    /// rejection of stale or replayed notifications.
+    pub fn sign_with(
+        &self,
+        timestamp: impl Into<String>,
+        nonce: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Result<SignedNotification> {
+        let (timestamp, nonce, body) = (timestamp.into(), nonce.into(), body.into());
+        let payload = format!("{}\n{}\n{}\n", timestamp, nonce, body);
+        let mut signature = vec![0; self.key_pair.public_modulus_len()];
+        self.key_pair
+            .sign(
+                &signature::RSA_PKCS1_SHA256,
+                &rand::SystemRandom::new(),
+                payload.as_bytes(),
+                &mut signature,
+            )
+            .map_err(|e| Error::Msg(format!("Could not sign the notification: {e}")))?;
+        Ok(SignedNotification {
+            headers: vec![
+                ("BinancePay-Timestamp".into(), timestamp),
+                ("BinancePay-Nonce".into(), nonce),
+                (
+                    "BinancePay-Certificate-SN".into(),
+                    self.certificate.cert_serial.clone(),
+                ),
+                ("BinancePay-Signature".into(), base64::encode(signature)),
+            ],
+            body,
+        })
+    }
+}
+
+/// Body of the request Binance sends the notification with, the details are serialized into
+/// the `data` JSON string.
+pub fn notification_body(notification: &Notification) -> Result<String> {
+    let (biz_type, biz_id, biz_status, data) = match notification {
+        Notification::Order {
+            biz_id,
+            biz_status,
+            order_detail,
+        } => (
+            "PAY",
+            biz_id,
+            serde_json::to_value(biz_status)?,
+            serde_json::to_string(order_detail)?,
+        ),
+        Notification::Refund {
+            biz_id,
+            biz_status,
+            refund_detail,
+        } => (
+            "PAY_REFUND",
+            biz_id,
+            serde_json::to_value(biz_status)?,
+            serde_json::to_string(refund_detail)?,
+        ),
+        Notification::Payout {
+            biz_id,
+            biz_status,
+            payout_detail,
+        } => (
+            "PAYOUT",
+            biz_id,
+            serde_json::to_value(biz_status)?,
+            serde_json::to_string(payout_detail)?,
+        ),
+        Notification::Unknown {
+            biz_type,
+            biz_id,
+            biz_status,
+            raw_data,
+        } => (
+            biz_type.as_str(),
+            biz_id,
+            Value::String(biz_status.clone()),
+            raw_data.clone(),
+        ),
+    };
+    Ok(serde_json::to_string(&Body {
+        biz_type,
+        data,
+        biz_id: *biz_id,
+        biz_status,
+    })?)
+}
+
+/// Written with a struct rather than a [`Value`], which can't hold the 20 digits `bizId`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Body<'a> {
+    biz_type: &'a str,
+    data: String,
+    biz_id: u128,
+    biz_status: Value,
+}
+
+fn strip_pem(pem: &str) -> String {
+    pem.split('\n')
+        .filter(|line| !line.starts_with("-----"))
+        .collect::<Vec<&str>>()
+        .join("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c2b::webhook::verification::{VerificationError, Verify};
+
+    const REFUND: &str = r#"{"bizType":"PAY_REFUND","data":"{\"merchantTradeNo\":\"6177e6ae81ce6f001b4a6233\",\"totalFee\":0.01,\"transactTime\":1635248421335,\"refundInfo\":{\"orderAmount\":\"0.01000000\",\"duplicateRequest\":\"N\",\"payerOpenId\":\"9aa0a8bb21cf5fbf049aad7db35dc3d3\",\"prepayId\":\"123289163323899904\",\"refundRequestId\":\"68711039982968853\",\"refundedAmount\":\"0.01000000\",\"remainingAttempts\":9,\"refundAmount\":\"0.01000000\"},\"currency\":\"BUSD\",\"commission\":0,\"openUserId\":\"b5ec36baaa5ab9a5cfb1c29c2057bd81\",\"productType\":\"LIVE_STREAM\",\"productName\":\"LIVE_STREAM\",\"tradeType\":\"APP\"}","bizId":123289163323899904,"bizStatus":"REFUND_SUCCESS"}"#;
+
+    #[test]
+    fn test_generated_key_pair_signs_verifiable_notifications() {
+        let signer = KeyPair::generate().unwrap().signer().unwrap();
+        let signed = signer
+            .sign_notification(&Notification::try_from(REFUND).unwrap())
+            .unwrap();
+        let verifier = signer.verifier().unwrap();
+        verifier
+            .verify(&signed.header_map().unwrap(), &signed.body)
+            .unwrap();
+        match Notification::try_from(signed.body.as_str()).unwrap() {
+            Notification::Refund {
+                biz_id,
+                refund_detail,
+                ..
+            } => {
+                assert_eq!(biz_id, 123289163323899904);
+                assert_eq!(refund_detail.total_fee.to_string(), "0.01");
+                assert_eq!(
+                    refund_detail.refund_info.refund_request_id,
+                    "68711039982968853"
+                );
+            }
+            _ => panic!("Unexpected notification type"),
+        }
+
+        // The bundled key pair doesn't match the generated certificate.
+        let other = KeyPair::bundled().unwrap().signer().unwrap();
+        let signed = other
+            .sign_with("1655972405000", "nonce", signed.body)
+            .unwrap();
+        assert!(matches!(
+            verifier.verify(&signed.headers, &signed.body),
+            Err(VerificationError::SerialMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_notifications_keep_their_raw_data() {
+        let notification = Notification::Unknown {
+            biz_type: "PAY_LATER".into(),
+            biz_id: 1,
+            biz_status: "DUE".into(),
+            raw_data: r#"{"due":1}"#.into(),
+        };
+        let body = notification_body(&notification).unwrap();
+        match Notification::try_from(body.as_str()).unwrap() {
+            Notification::Unknown {
+                biz_type, raw_data, ..
+            } => {
+                assert_eq!(biz_type, "PAY_LATER");
+                assert_eq!(raw_data, r#"{"due":1}"#);
+            }
+            _ => panic!("Unexpected notification type"),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! HTTP transport used by the [`Client`](crate::client::Client) to send the signed requests.
+/*!
+The client only hands over a ready to send [`HttpRequest`] and reads back the [`HttpResponse`],
+signing and response parsing stay in the client. Implement [`Transport`] to plug in another
+HTTP stack, [`ReqwestTransport`] is used by default.
+
+```
+# use bpay::api::wallet_balance::query::{Request, WalletType};
+# use bpay::c2b::currency::Currency;
+# use bpay::client::Client;
+# use bpay::transport::{HttpResponse, InMemoryTransport};
+# #[tokio::main]
+# async fn main() -> bpay::errors::Result<()> {
+let transport = InMemoryTransport::new(|_request| {
+    HttpResponse::ok(r#"{"status":"SUCCESS","code":"000000","data":{"balance":1.5,"asset":"BUSD","fiat":"USD","availableFiatValuation":1.5,"availableBtcValuation":0.0}}"#)
+});
+let client = Client::builder().transport(transport).build()?;
+let request = Request {
+    wallet: WalletType::FundingWallet,
+    currency: Currency::BUSD,
+};
+assert_eq!(request.query(&client).await?.balance.to_string(), "1.5");
+# Ok(())
+# }
+```
+*/
+
+use crate::errors::Result;
+use std::sync::Mutex;
+use url::Url;
+
+/// A signed POST request ready to be sent.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub url: Url,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpRequest {
+    /// Value of the first header with the given name, compared case insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// Raw response as received by the transport.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".into(), "application/json".into())],
+            body: body.into(),
+        }
+    }
+
+    /// Response with the `200` status code.
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self::new(200, body)
+    }
+
+    /// Value of the first header with the given name, compared case insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// Sends the requests of the client over the wire.
+/// Failures of the underlying HTTP stack are reported as [`Error::Transport`](crate::errors::Error::Transport).
+#[async_trait::async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse>;
+}
+
+/// Transport backed by a [`reqwest::Client`].
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let mut builder = self.client.post(request.url).body(request.body);
+        for (key, val) in &request.headers {
+            builder = builder.header(key.as_str(), val.as_str());
+        }
+        let response = builder.send().await?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(key, val)| Some((key.to_string(), val.to_str().ok()?.to_string())))
+            .collect();
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: response.bytes().await?.to_vec(),
+        })
+    }
+}
+
+type Handler = Box<dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync>;
+
+/// Transport answering every request in process, meant for tests.
+/// All the requests it received are kept, see [`InMemoryTransport::requests`].
+pub struct InMemoryTransport {
+    handler: Handler,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl InMemoryTransport {
+    pub fn new(handler: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static) -> Self {
+        Self {
+            handler: Box::new(handler),
+            requests: Mutex::new(vec![]),
+        }
+    }
+
+    /// Requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for InMemoryTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let response = (self.handler)(&request);
+        self.requests.lock().unwrap().push(request);
+        Ok(response)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: Transport + ?Sized> Transport for std::sync::Arc<T> {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        (**self).send(request).await
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, val)| val.as_str())
+}